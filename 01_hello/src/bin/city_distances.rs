@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// Prints the distance matrix of the 1.2.3 sample cities:
+// `cargo run --manifest-path ./01_hello/Cargo.toml --bin city_distances`
+
+use _1_hello::city::{self, City};
+
+// Distances in km between every pair of cities, one row per city
+fn distance_matrix(cities: &[City]) -> String {
+    let width = cities.iter().map(|city| city.name.len()).max().unwrap_or(0);
+    let mut matrix = format!("{:width$}", "", width = width);
+
+    for city in cities {
+        matrix += &format!(" {:>width$}", city.name, width = width);
+    }
+
+    for from in cities {
+        matrix += &format!("\n{:width$}", from.name, width = width);
+
+        for to in cities {
+            matrix += &format!(" {:>width$.0}", from.distance_km(to), width = width);
+        }
+    }
+
+    matrix
+}
+
+fn main() {
+    let cities = city::sample_cities();
+
+    for city in cities.iter() {
+        println!("{:#}", city);
+    }
+
+    println!();
+    println!("Distances in km:");
+    println!("{}", distance_matrix(&cities));
+}
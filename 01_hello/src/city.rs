@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 1.2.3 `City` moved here, its coordinates can be read back by
+//       `Coordinate`, which also measures great-circle distances and bearings
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::template::Value;
+
+// Mean radius of the Earth
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+pub struct City {
+    pub name: &'static str,
+    // Latitude
+    pub lat: f32,
+    // Longitude
+    pub lon: f32,
+}
+
+impl City {
+    // `Display` as a `Template`, rendered with `template_values`
+    pub const DISPLAY_TEMPLATE: &'static str = "{name}: {lat:.3}°{ns} {lon:.3}°{ew}";
+
+    // `name`, `lat` and `lon` in degrees without sign, and their hemispheres
+    // `ns` and `ew`
+    pub fn template_values(&self) -> HashMap<&'static str, Value> {
+        vec![
+            ("name", self.name.into()),
+            ("lat", self.lat.abs().into()),
+            ("lon", self.lon.abs().into()),
+            ("ns", if self.lat >= 0.0 { 'N' } else { 'S' }.into()),
+            ("ew", if self.lon >= 0.0 { 'E' } else { 'W' }.into()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    pub fn coordinate(&self) -> Coordinate {
+        Coordinate {
+            lat: self.lat.into(),
+            lon: self.lon.into(),
+        }
+    }
+
+    pub fn distance_km(&self, other: &City) -> f64 {
+        self.coordinate().distance_km(&other.coordinate())
+    }
+
+    pub fn bearing(&self, other: &City) -> f64 {
+        self.coordinate().bearing(&other.coordinate())
+    }
+}
+
+impl Display for City {
+    // `f` is a buffer, and this method must write the formatted string into it
+    // NOTE: `{:#}` prints the coordinates in degrees, minutes and seconds
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+
+        Display::fmt(&self.coordinate(), f)
+    }
+}
+
+// The cities of the 1.2.3 example
+// NOTE: the course values, even though `f32` doesn't hold all of their digits
+#[allow(clippy::excessive_precision)]
+pub fn sample_cities() -> [City; 3] {
+    [
+        City {
+            name: "Dublin",
+            lat: 53.347778,
+            lon: -6.259722,
+        },
+        City {
+            name: "Oslo",
+            lat: 59.95,
+            lon: 10.75,
+        },
+        City {
+            name: "Vancouver",
+            lat: 49.25,
+            lon: -123.1,
+        },
+    ]
+}
+
+// Latitude and longitude in decimal degrees, north and east are positive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinate {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Coordinate {
+    // Haversine great-circle distance
+    pub fn distance_km(&self, other: &Coordinate) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    // Initial bearing of the great circle towards `other`, in degrees
+    // clockwise from the north, `[0, 360)`
+    pub fn bearing(&self, other: &Coordinate) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let y = d_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+}
+
+// `53.348°N 6.260°W` by default, the precision can be changed, e.g. `{:.1}`,
+// `{:#}` prints degrees, minutes and rounded seconds: `53°20'52"N 6°15'35"W`
+impl Display for Coordinate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+
+        // `write!` is like `format!`, but it will write the formatted string
+        // into a buffer (the first argument)
+        if f.alternate() {
+            write_dms(f, self.lat, lat_c)?;
+            write!(f, " ")?;
+            write_dms(f, self.lon, lon_c)
+        } else {
+            let precision = f.precision().unwrap_or(3);
+
+            write!(
+                f,
+                "{:.*}°{} {:.*}°{}",
+                precision,
+                self.lat.abs(),
+                lat_c,
+                precision,
+                self.lon.abs(),
+                lon_c
+            )
+        }
+    }
+}
+
+fn write_dms(f: &mut Formatter, degrees: f64, hemisphere: char) -> fmt::Result {
+    // Round the total first, so `59.9999` carries into `60°0'0"`
+    let seconds = (degrees.abs() * 3600.0).round() as u64;
+
+    write!(
+        f,
+        "{}°{}'{}\"{}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        hemisphere
+    )
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseCoordinateError {
+    // Latitude and longitude can't be told apart
+    MissingComponent(String),
+    InvalidAngle(String),
+    // Minutes or seconds of `60` and more
+    InvalidMinutesOrSeconds(String),
+    // A negative angle with a hemisphere, e.g. `-53°N`
+    SignAndHemisphere(String),
+    OutOfRange(String),
+}
+
+impl Display for ParseCoordinateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseCoordinateError::MissingComponent(s) => {
+                write!(f, "expected latitude and longitude in `{}`", s)
+            }
+            ParseCoordinateError::InvalidAngle(s) => write!(f, "invalid angle `{}`", s),
+            ParseCoordinateError::InvalidMinutesOrSeconds(s) => {
+                write!(f, "minutes and seconds must be less than 60 in `{}`", s)
+            }
+            ParseCoordinateError::SignAndHemisphere(s) => {
+                write!(f, "both a sign and a hemisphere in `{}`", s)
+            }
+            ParseCoordinateError::OutOfRange(s) => write!(f, "angle out of range `{}`", s),
+        }
+    }
+}
+
+impl error::Error for ParseCoordinateError {}
+
+// Latitude first, then longitude, either as:
+//   - signed decimal degrees: `53.347778, -6.259722` or `53.347778 -6.259722`
+//   - decimal degrees with hemispheres: `53.348°N 6.260°W`
+//   - degrees, minutes and seconds: `53°20'52"N 6°15'35"W` (`′` and `″` work
+//     too, minutes and seconds are optional)
+impl FromStr for Coordinate {
+    type Err = ParseCoordinateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let (lat, lon) = if let Some(comma) = s.find(',') {
+            (&s[..comma], &s[comma + 1..])
+        } else if let Some(hemisphere) = s.find(['N', 'S', 'n', 's']) {
+            s.split_at(hemisphere + 1)
+        } else {
+            let mut parts = s.split_whitespace();
+
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(lat), Some(lon), None) => (lat, lon),
+                _ => return Err(ParseCoordinateError::MissingComponent(s.to_owned())),
+            }
+        };
+
+        Ok(Coordinate {
+            lat: parse_angle(lat, ('N', 'S'), 90.0)?,
+            lon: parse_angle(lon, ('E', 'W'), 180.0)?,
+        })
+    }
+}
+
+fn parse_angle(
+    s: &str,
+    (positive, negative): (char, char),
+    limit: f64,
+) -> Result<f64, ParseCoordinateError> {
+    let s = s.trim();
+    let invalid = || ParseCoordinateError::InvalidAngle(s.to_owned());
+
+    let (rest, hemisphere) = match s.chars().last() {
+        Some(c) if c.eq_ignore_ascii_case(&positive) => (&s[..s.len() - 1], Some(1.0)),
+        Some(c) if c.eq_ignore_ascii_case(&negative) => (&s[..s.len() - 1], Some(-1.0)),
+        _ => (s, None),
+    };
+
+    // Degrees, minutes and seconds, each number may be followed by its unit
+    let rest = rest.trim();
+    let mut parts = [0.0; 3];
+    let mut count = 0;
+    let mut chars = rest.char_indices().peekable();
+    let mut sign = 1.0;
+
+    if let Some(&(_, c @ ('-' | '+'))) = chars.peek() {
+        sign = if c == '-' { -1.0 } else { 1.0 };
+        chars.next();
+    }
+
+    while let Some(&(start, _)) = chars.peek() {
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_ascii_digit() || c == '.') {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+
+        let number = rest[start..end].parse::<f64>().map_err(|_| invalid())?;
+
+        let index = match chars.peek().map(|&(_, c)| c) {
+            Some('°') => 0,
+            Some('\'') | Some('′') => 1,
+            Some('"') | Some('″') => 2,
+            _ => count,
+        };
+        if let Some(&(_, '°' | '\'' | '′' | '"' | '″')) = chars.peek() {
+            chars.next();
+        }
+
+        // Units have to come in order: degrees, minutes, seconds
+        if index < count || index > 2 {
+            return Err(invalid());
+        }
+        parts[index] = number;
+        count = index + 1;
+
+        while let Some(&(_, c)) = chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            chars.next();
+        }
+    }
+
+    let [degrees, minutes, seconds] = parts;
+
+    if count == 0 {
+        return Err(invalid());
+    }
+    if minutes >= 60.0 || seconds >= 60.0 {
+        return Err(ParseCoordinateError::InvalidMinutesOrSeconds(s.to_owned()));
+    }
+    if sign < 0.0 && hemisphere.is_some() {
+        return Err(ParseCoordinateError::SignAndHemisphere(s.to_owned()));
+    }
+
+    let angle = sign * hemisphere.unwrap_or(1.0) * (degrees + minutes / 60.0 + seconds / 3600.0);
+
+    if angle.abs() > limit {
+        return Err(ParseCoordinateError::OutOfRange(s.to_owned()));
+    }
+
+    Ok(angle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinate(s: &str) -> Coordinate {
+        s.parse().unwrap()
+    }
+
+    fn assert_close(a: f64, b: f64, epsilon: f64) {
+        assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_hemispheres() {
+        let dublin = Coordinate {
+            lat: 53.348,
+            lon: -6.26,
+        };
+
+        assert_eq!(coordinate("53.348°N 6.260°W"), dublin);
+        assert_eq!(coordinate("53.348n 6.26w"), dublin);
+        assert_eq!(coordinate("53.348, -6.26"), dublin);
+        assert_eq!(coordinate("53.348 -6.26"), dublin);
+        assert_eq!(
+            coordinate("33.9°S 151.2°E"),
+            Coordinate {
+                lat: -33.9,
+                lon: 151.2
+            }
+        );
+
+        let dms = coordinate("53°20'52\"N 6°15'35\"W");
+        assert_close(dms.lat, 53.0 + 20.0 / 60.0 + 52.0 / 3600.0, 1e-12);
+        assert_close(dms.lon, -(6.0 + 15.0 / 60.0 + 35.0 / 3600.0), 1e-12);
+        assert_eq!(coordinate("49°15′N 123°6′W"), coordinate("49.25 -123.1"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s: &str| s.parse::<Coordinate>().unwrap_err().to_string();
+
+        assert_eq!(error("91°N 0°E"), "angle out of range `91°N`");
+        assert_eq!(error("-90.5, 0"), "angle out of range `-90.5`");
+        assert_eq!(error("0, 180.5"), "angle out of range `180.5`");
+        assert_eq!(error("0°N 181°W"), "angle out of range `181°W`");
+        assert!(coordinate("90°S 180°E").lat == -90.0);
+
+        assert_eq!(
+            error("53°60'N 6°W"),
+            "minutes and seconds must be less than 60 in `53°60'N`"
+        );
+        assert_eq!(
+            error("-53°N 6°W"),
+            "both a sign and a hemisphere in `-53°N`"
+        );
+        assert_eq!(error("53.3"), "expected latitude and longitude in `53.3`");
+        assert_eq!(error("north, east"), "invalid angle `north`");
+    }
+
+    #[test]
+    fn test_display() {
+        let [dublin, oslo, _] = sample_cities();
+
+        assert_eq!(dublin.to_string(), "Dublin: 53.348°N 6.260°W");
+        assert_eq!(format!("{:#}", dublin), "Dublin: 53°20'52\"N 6°15'35\"W");
+        assert_eq!(format!("{:.1}", oslo.coordinate()), "60.0°N 10.8°E");
+
+        // The output parses back
+        let back = coordinate(&format!("{:#}", dublin.coordinate()));
+        assert_close(back.lat, dublin.lat.into(), 1.0 / 3600.0);
+        assert_close(back.lon, dublin.lon.into(), 1.0 / 3600.0);
+
+        // Rounding carries into the minutes and degrees
+        assert_eq!(
+            format!("{:#}", coordinate("59.99999, -0.00001")),
+            "60°0'0\"N 0°0'0\"W"
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let [dublin, oslo, _] = sample_cities();
+
+        // About 1,267 km between Dublin and Oslo
+        assert_close(dublin.distance_km(&oslo), 1267.0, 1.0);
+        assert_close(oslo.distance_km(&dublin), dublin.distance_km(&oslo), 1e-9);
+        assert_eq!(dublin.distance_km(&dublin), 0.0);
+
+        // A degree of latitude, and half of the circumference
+        let equator = coordinate("0, 0");
+        assert_close(
+            equator.distance_km(&coordinate("1, 0")),
+            EARTH_RADIUS_KM.to_radians(),
+            1e-9,
+        );
+        assert_close(
+            equator.distance_km(&coordinate("0, 180")),
+            EARTH_RADIUS_KM * std::f64::consts::PI,
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_bearing() {
+        let start = coordinate("10, 20");
+
+        assert_close(start.bearing(&coordinate("30, 20")), 0.0, 1e-9);
+        assert_close(start.bearing(&coordinate("-30, 20")), 180.0, 1e-9);
+        assert_close(coordinate("0, 0").bearing(&coordinate("0, 10")), 90.0, 1e-9);
+        assert_close(
+            coordinate("0, 0").bearing(&coordinate("0, -10")),
+            270.0,
+            1e-9,
+        );
+
+        let [dublin, oslo, _] = sample_cities();
+        assert_close(dublin.bearing(&oslo), 47.86, 0.01);
+    }
+}
@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 1.2.3 `Color` moved here, it parses its own `Display` output,
+//       `#RRGGBB`, `#RGB` and CSS named colors, converts to and from HSL and
+//       HSV, blends and calculates WCAG contrast ratios
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use _1_hello::template::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+// Hue in degrees `[0, 360)`, saturation and lightness in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+}
+
+// Hue in degrees `[0, 360)`, saturation and value in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub hue: f64,
+    pub saturation: f64,
+    pub value: f64,
+}
+
+// The format string of `Display`, `write!` only takes a literal, so it is a
+// macro rather than the constant itself
+macro_rules! display_template {
+    () => {
+        "RGB ({r}, {g}, {b}) 0x{r:02X}{g:02X}{b:02X}"
+    };
+}
+
+impl Color {
+    // `Display` as a `Template`, rendered with `template_values`
+    pub const DISPLAY_TEMPLATE: &'static str = display_template!();
+
+    // `r`, `g`, `b` and `hex`
+    pub fn template_values(&self) -> HashMap<&'static str, Value> {
+        vec![
+            ("r", self.red.into()),
+            ("g", self.green.into()),
+            ("b", self.blue.into()),
+            ("hex", self.hex().into()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    pub fn from_hex(hex: u32) -> Color {
+        Color {
+            red: (hex >> 16) as u8,
+            green: (hex >> 8) as u8,
+            blue: hex as u8,
+        }
+    }
+
+    pub fn hex(&self) -> u32 {
+        u32::from(self.red) << 16 | u32::from(self.green) << 8 | u32::from(self.blue)
+    }
+
+    pub fn to_hsl(self) -> Hsl {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+
+        Hsl {
+            hue,
+            saturation,
+            lightness,
+        }
+    }
+
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let chroma = (1.0 - (2.0 * hsl.lightness - 1.0).abs()) * hsl.saturation;
+
+        Color::from_hue(hsl.hue, chroma, hsl.lightness - chroma / 2.0)
+    }
+
+    pub fn to_hsv(self) -> Hsv {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        Hsv {
+            hue,
+            saturation,
+            value: max,
+        }
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let chroma = hsv.value * hsv.saturation;
+
+        Color::from_hue(hsv.hue, chroma, hsv.value - chroma)
+    }
+
+    // Paint `top` over this color with the opacity `alpha` in `[0, 1]`
+    pub fn blend(&self, top: Color, alpha: f64) -> Color {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let mix = |bottom: u8, top: u8| {
+            (f64::from(top) * alpha + f64::from(bottom) * (1.0 - alpha)).round() as u8
+        };
+
+        Color {
+            red: mix(self.red, top.red),
+            green: mix(self.green, top.green),
+            blue: mix(self.blue, top.blue),
+        }
+    }
+
+    // WCAG 2 relative luminance, `0` for black and `1` for white
+    pub fn luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = f64::from(channel) / 255.0;
+
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    // WCAG 2 contrast ratio from `1` (no contrast) to `21` (black on white)
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    // Hue in degrees with the maximum and the minimum channels in `[0, 1]`
+    fn hue_max_min(&self) -> (f64, f64, f64) {
+        let r = f64::from(self.red) / 255.0;
+        let g = f64::from(self.green) / 255.0;
+        let b = f64::from(self.blue) / 255.0;
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, max, min)
+    }
+
+    // Shared tail of the HSL and HSV conversions
+    fn from_hue(hue: f64, chroma: f64, offset: f64) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f64| ((c + offset) * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        Color {
+            red: channel(r),
+            green: channel(g),
+            blue: channel(b),
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            display_template!(),
+            r = self.red,
+            g = self.green,
+            b = self.blue,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseColorError {
+    // `#` followed by something else than 3 or 6 hex digits
+    InvalidHex(String),
+    // `RGB (...)` without three `u8` channels
+    InvalidRgb(String),
+    // The `0x...` part of `RGB (...) 0x...` disagrees with the channels
+    HexMismatch(String),
+    UnknownName(String),
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidHex(s) => write!(f, "invalid hex color `{}`", s),
+            ParseColorError::InvalidRgb(s) => write!(f, "invalid RGB color `{}`", s),
+            ParseColorError::HexMismatch(s) => {
+                write!(f, "hex part doesn't match the channels in `{}`", s)
+            }
+            ParseColorError::UnknownName(s) => write!(f, "unknown color name `{}`", s),
+        }
+    }
+}
+
+impl error::Error for ParseColorError {}
+
+// Accepts `RGB (128, 255, 90) 0x80FF5A` (the `0x...` part is optional),
+// `#80FF5A`, `#8F5` and CSS named colors, all case-insensitive
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(digits) = s.strip_prefix('#') {
+            parse_hex(digits).ok_or_else(|| ParseColorError::InvalidHex(s.to_owned()))
+        } else if s.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("RGB")) {
+            parse_rgb(s)
+        } else {
+            NAMED_COLORS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(s))
+                .map(|&(_, hex)| Color::from_hex(hex))
+                .ok_or_else(|| ParseColorError::UnknownName(s.to_owned()))
+        }
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<Color> {
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let hex = u32::from_str_radix(digits, 16).ok()?;
+
+    match digits.len() {
+        6 => Some(Color::from_hex(hex)),
+        // Every digit is doubled, `#8F5` is `#88FF55`
+        3 => Some(Color {
+            red: ((hex >> 8) & 0xF) as u8 * 0x11,
+            green: ((hex >> 4) & 0xF) as u8 * 0x11,
+            blue: (hex & 0xF) as u8 * 0x11,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_rgb(s: &str) -> Result<Color, ParseColorError> {
+    let invalid = || ParseColorError::InvalidRgb(s.to_owned());
+
+    let rest = s[3..].trim_start().strip_prefix('(').ok_or_else(invalid)?;
+    let close = rest.find(')').ok_or_else(invalid)?;
+
+    let channels = rest[..close]
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+
+    let color = match channels.as_slice() {
+        [red, green, blue] => Color {
+            red: *red,
+            green: *green,
+            blue: *blue,
+        },
+        _ => return Err(invalid()),
+    };
+
+    match rest[close + 1..].trim() {
+        "" => Ok(color),
+        hex => {
+            let digits = hex
+                .strip_prefix("0x")
+                .or_else(|| hex.strip_prefix("0X"))
+                .filter(|digits| digits.len() == 6)
+                .ok_or_else(invalid)?;
+
+            match parse_hex(digits) {
+                Some(parsed) if parsed == color => Ok(color),
+                Some(_) => Err(ParseColorError::HexMismatch(s.to_owned())),
+                None => Err(invalid()),
+            }
+        }
+    }
+}
+
+// CSS Color Module Level 4 named colors
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use _1_hello::template::Template;
+
+    // Every 3rd value of each channel, plus the maximum
+    fn samples() -> impl Iterator<Item = Color> {
+        let channel = || (0..=255u8).step_by(3).chain(Some(255));
+
+        channel().flat_map(move |red| {
+            channel().flat_map(move |green| channel().map(move |blue| Color { red, green, blue }))
+        })
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for color in samples() {
+            assert_eq!(color.to_string().parse(), Ok(color));
+        }
+    }
+
+    #[test]
+    fn test_display_template() {
+        let template = Template::parse(Color::DISPLAY_TEMPLATE).unwrap();
+
+        for color in samples().step_by(97) {
+            assert_eq!(
+                template.render(&color.template_values()),
+                Ok(color.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_hsl_hsv_round_trip() {
+        for color in samples() {
+            assert_eq!(Color::from_hsl(color.to_hsl()), color);
+            assert_eq!(Color::from_hsv(color.to_hsv()), color);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let color = Color {
+            red: 128,
+            green: 255,
+            blue: 90,
+        };
+
+        assert_eq!("RGB (128, 255, 90) 0x80FF5A".parse(), Ok(color));
+        assert_eq!("rgb(128,255,90)".parse(), Ok(color));
+        assert_eq!("#80ff5a".parse(), Ok(color));
+        assert_eq!("#8F5".parse(), Ok(Color::from_hex(0x88FF55)));
+        assert_eq!("RebeccaPurple".parse(), Ok(Color::from_hex(0x663399)));
+        assert_eq!(
+            "RGB (128, 255, 90) 0x000000".parse::<Color>(),
+            Err(ParseColorError::HexMismatch(
+                "RGB (128, 255, 90) 0x000000".to_owned()
+            ))
+        );
+        assert!("RGB (256, 0, 0)".parse::<Color>().is_err());
+        assert!("#80FF5".parse::<Color>().is_err());
+        assert!("#+8F5".parse::<Color>().is_err());
+        assert!("ultraviolet".parse::<Color>().is_err());
+        assert!("él".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_hsl_hsv() {
+        let hsl = Color::from_hex(0xFF0000).to_hsl();
+        assert_eq!((hsl.hue, hsl.saturation, hsl.lightness), (0.0, 1.0, 0.5));
+
+        let hsv = Color::from_hex(0x00FF00).to_hsv();
+        assert_eq!((hsv.hue, hsv.saturation, hsv.value), (120.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_blend_and_contrast() {
+        let black = Color::from_hex(0x000000);
+        let white = Color::from_hex(0xFFFFFF);
+
+        assert_eq!(black.blend(white, 0.5), Color::from_hex(0x808080));
+        assert_eq!(black.blend(white, 0.0), black);
+        assert_eq!(black.blend(white, 2.0), white);
+
+        assert!((black.contrast_ratio(&white) - 21.0).abs() < 1e-9);
+        assert!((white.contrast_ratio(&black) - 21.0).abs() < 1e-9);
+        assert_eq!(white.contrast_ratio(&white), 1.0);
+    }
+}
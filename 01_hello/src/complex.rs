@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 1.2.2 `Complex` activity and the 20.8 FFI `Complex` merged into a
+//       generic complex number with arithmetic and elementary functions
+//       written in pure Rust
+//       The layout is `#[repr(C)]`, so `Complex<f32>` and `Complex<f64>` can
+//       still be passed to libm's `csqrtf`/`ccosf` and `csqrt`/`ccos`
+//       20_std_misc uses it through the library target of 01_hello
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// The floating point operations the elementary functions need
+pub trait Float:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sinh(self) -> Self;
+    fn cosh(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn is_sign_negative(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+                const TWO: Self = 2.0;
+
+                fn sqrt(self) -> Self { <$t>::sqrt(self) }
+                fn exp(self) -> Self { <$t>::exp(self) }
+                fn ln(self) -> Self { <$t>::ln(self) }
+                fn sin(self) -> Self { <$t>::sin(self) }
+                fn cos(self) -> Self { <$t>::cos(self) }
+                fn sinh(self) -> Self { <$t>::sinh(self) }
+                fn cosh(self) -> Self { <$t>::cosh(self) }
+                fn atan2(self, other: Self) -> Self { <$t>::atan2(self, other) }
+                fn hypot(self, other: Self) -> Self { <$t>::hypot(self, other) }
+                fn is_sign_negative(self) -> bool { <$t>::is_sign_negative(self) }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+// Same layout as C99 `float complex`/`double complex`: real part first
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex<T> {
+    pub real: T,
+    pub imag: T,
+}
+
+impl<T> Complex<T> {
+    pub const fn new(real: T, imag: T) -> Complex<T> {
+        Complex { real, imag }
+    }
+}
+
+impl<T: Float> Complex<T> {
+    pub const I: Complex<T> = Complex::new(T::ZERO, T::ONE);
+
+    // `r * (cos(theta) + i sin(theta))`
+    pub fn from_polar(r: T, theta: T) -> Complex<T> {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    // `(abs, arg)`
+    pub fn to_polar(self) -> (T, T) {
+        (self.abs(), self.arg())
+    }
+
+    pub fn conj(self) -> Complex<T> {
+        Complex::new(self.real, -self.imag)
+    }
+
+    // Squared modulus, cheaper than `abs` when only comparing
+    pub fn norm_sqr(self) -> T {
+        self.real * self.real + self.imag * self.imag
+    }
+
+    // Modulus, `hypot` avoids overflowing the intermediate squares
+    pub fn abs(self) -> T {
+        self.real.hypot(self.imag)
+    }
+
+    // Argument in `(-pi, pi]`
+    pub fn arg(self) -> T {
+        self.imag.atan2(self.real)
+    }
+
+    pub fn exp(self) -> Complex<T> {
+        Complex::from_polar(self.real.exp(), self.imag)
+    }
+
+    // Principal branch, the cut runs along the negative real axis
+    pub fn ln(self) -> Complex<T> {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    // Principal square root, the real part is never negative
+    // NOTE: computed from the modulus rather than the polar form, so that
+    //       e.g. `sqrt(-1)` is exactly `i`, the sign of the imaginary part
+    //       follows the sign of `imag` (even `-0.0`) as in C99 `csqrt`
+    pub fn sqrt(self) -> Complex<T> {
+        let r = self.abs();
+        let real = ((r + self.real) / T::TWO).sqrt();
+        let imag = ((r - self.real) / T::TWO).sqrt();
+
+        if self.imag.is_sign_negative() {
+            Complex::new(real, -imag)
+        } else {
+            Complex::new(real, imag)
+        }
+    }
+
+    // Principal value of `self^exponent`, `0^0` is `1`
+    pub fn pow(self, exponent: Complex<T>) -> Complex<T> {
+        let zero = Complex::new(T::ZERO, T::ZERO);
+
+        if self == zero {
+            return if exponent == zero {
+                Complex::new(T::ONE, T::ZERO)
+            } else {
+                zero
+            };
+        }
+
+        (exponent * self.ln()).exp()
+    }
+
+    pub fn sin(self) -> Complex<T> {
+        Complex::new(
+            self.real.sin() * self.imag.cosh(),
+            self.real.cos() * self.imag.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Complex<T> {
+        Complex::new(
+            self.real.cos() * self.imag.cosh(),
+            -(self.real.sin() * self.imag.sinh()),
+        )
+    }
+}
+
+impl<T: Default> From<T> for Complex<T> {
+    fn from(real: T) -> Self {
+        Complex {
+            real,
+            imag: T::default(),
+        }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Complex<T> {
+    type Output = Complex<T>;
+
+    fn add(self, rhs: Complex<T>) -> Complex<T> {
+        Complex::new(self.real + rhs.real, self.imag + rhs.imag)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Complex<T> {
+    type Output = Complex<T>;
+
+    fn sub(self, rhs: Complex<T>) -> Complex<T> {
+        Complex::new(self.real - rhs.real, self.imag - rhs.imag)
+    }
+}
+
+impl<T> Mul for Complex<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    type Output = Complex<T>;
+
+    fn mul(self, rhs: Complex<T>) -> Complex<T> {
+        Complex::new(
+            self.real * rhs.real - self.imag * rhs.imag,
+            self.real * rhs.imag + self.imag * rhs.real,
+        )
+    }
+}
+
+impl<T> Div for Complex<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    type Output = Complex<T>;
+
+    // Multiply both sides by the conjugate of `rhs`
+    fn div(self, rhs: Complex<T>) -> Complex<T> {
+        let denominator = rhs.real * rhs.real + rhs.imag * rhs.imag;
+
+        Complex::new(
+            (self.real * rhs.real + self.imag * rhs.imag) / denominator,
+            (self.imag * rhs.real - self.real * rhs.imag) / denominator,
+        )
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Complex<T> {
+    type Output = Complex<T>;
+
+    fn neg(self) -> Complex<T> {
+        Complex::new(-self.real, -self.imag)
+    }
+}
+
+// `3.3 + 7.2i`, a negative imaginary part prints as `3.3 - 7.2i`
+// NOTE: the precision applies to both parts, e.g. `{:.2}`
+impl<T: Float + Display> Display for Complex<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (sign, imag) = if self.imag.is_sign_negative() {
+            ('-', -self.imag)
+        } else {
+            ('+', self.imag)
+        };
+
+        match f.precision() {
+            Some(precision) => write!(
+                f,
+                "{:.*} {} {:.*}i",
+                precision, self.real, sign, precision, imag
+            ),
+            None => write!(f, "{} {} {}i", self.real, sign, imag),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::{E, FRAC_PI_2, PI};
+
+    const EPSILON: f64 = 1e-12;
+
+    fn assert_close(a: Complex<f64>, b: Complex<f64>) {
+        assert!((a - b).abs() < EPSILON, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Complex::new(3.3, 7.2).to_string(), "3.3 + 7.2i");
+        assert_eq!(Complex::new(3.3, -7.2).to_string(), "3.3 - 7.2i");
+        assert_eq!(format!("{:.2}", Complex::new(1.0f32, 0.5)), "1.00 + 0.50i");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Complex::new(1.0, 2.0);
+        let b = Complex::new(3.0, -4.0);
+
+        assert_eq!(a + b, Complex::new(4.0, -2.0));
+        assert_eq!(a - b, Complex::new(-2.0, 6.0));
+        assert_eq!(a * b, Complex::new(11.0, 2.0));
+        assert_close(a / b * b, a);
+        assert_eq!(-a, Complex::new(-1.0, -2.0));
+        assert_eq!(a * a.conj(), Complex::from(a.norm_sqr()));
+        assert_eq!(b.abs(), 5.0);
+    }
+
+    #[test]
+    fn test_polar() {
+        let z = Complex::from_polar(2.0, FRAC_PI_2);
+        assert_close(z, Complex::new(0.0, 2.0));
+
+        let (r, theta) = Complex::new(-1.0, 0.0).to_polar();
+        assert_eq!((r, theta), (1.0, PI));
+    }
+
+    #[test]
+    fn test_elementary_functions() {
+        // Euler's identity
+        assert_close((Complex::I * PI.into()).exp(), Complex::from(-1.0));
+        assert_close(Complex::from(E).ln(), Complex::from(1.0));
+        assert_close(Complex::<f64>::I.ln(), Complex::new(0.0, FRAC_PI_2));
+
+        assert_eq!(Complex::new(-1.0, 0.0).sqrt(), Complex::I);
+        assert_eq!(Complex::new(-1.0, -0.0).sqrt(), -Complex::I);
+        assert_eq!(Complex::new(-4.0f32, 0.0).sqrt(), Complex::new(0.0, 2.0));
+
+        // `i^i` is real
+        assert_close(
+            Complex::I.pow(Complex::I),
+            Complex::from((-FRAC_PI_2).exp()),
+        );
+        assert_eq!(
+            Complex::<f64>::default().pow(Complex::I),
+            Complex::default()
+        );
+
+        // `sin^2 + cos^2 = 1` holds off the real axis too
+        let z = Complex::new(0.5, -1.5);
+        let (sin, cos) = (z.sin(), z.cos());
+        assert_close(sin * sin + cos * cos, Complex::from(1.0));
+    }
+
+    #[test]
+    fn test_sqrt_squares_back() {
+        for real in -4..=4 {
+            for imag in -4..=4 {
+                let z = Complex::new(real as f64 / 2.0, imag as f64 / 2.0);
+                let root = z.sqrt();
+
+                assert!(root.real >= 0.0);
+                assert_close(root * root, z);
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: a bounded alternative to `{:#?}` for large nested values, e.g.
+//         Friend
+//         ├── person: Person
+//         │   ├── name: "Bob"
+//         │   └── age: 29
+//         └── friends: Vec
+//             └── [0]: Rc (seen)
+//       Values describe themselves to a `Visitor` through `Visit`, and
+//       `DebugTree` draws them with a maximum depth, a maximum number of
+//       children per node and a field filter
+//       Values behind an `Rc` are written in full only once, so reference
+//       cycles terminate, a value cut off by the limits is written again
+//       where it's reached next
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::rc::Rc;
+
+// How a child is named by its parent
+#[derive(Clone, Copy)]
+pub enum Label<'a> {
+    Field(&'static str),
+    Index(usize),
+    Key(&'a dyn Debug),
+}
+
+pub trait Visitor {
+    // A value without children, written with its `Debug`
+    fn leaf(&mut self, value: &dyn Debug);
+
+    fn node(&mut self, name: &str, children: &[(Label, &dyn Visit)]);
+
+    // A value that may be reachable more than once, identified by `ptr`
+    fn shared(&mut self, ptr: *const (), value: &dyn Visit);
+}
+
+pub trait Visit {
+    fn visit(&self, visitor: &mut dyn Visitor);
+}
+
+type FieldFilter<'a> = Box<dyn Fn(&str) -> bool + 'a>;
+
+pub struct DebugTree<'a> {
+    value: &'a dyn Visit,
+    max_depth: usize,
+    max_children: usize,
+    filter: Option<FieldFilter<'a>>,
+}
+
+impl<'a> DebugTree<'a> {
+    pub fn new(value: &'a dyn Visit) -> DebugTree<'a> {
+        DebugTree {
+            value,
+            max_depth: usize::MAX,
+            max_children: usize::MAX,
+            filter: None,
+        }
+    }
+
+    // Nodes deeper than `max_depth` are written without their children,
+    // the root is at depth 0
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Nodes with more children end with `… N more`
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        self.max_children = max_children;
+        self
+    }
+
+    // Only the struct fields for which `filter` returns `true` are written,
+    // indices and keys are always written
+    pub fn filter_fields<F: Fn(&str) -> bool + 'a>(mut self, filter: F) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+}
+
+impl Display for DebugTree<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut printer = Printer {
+            tree: self,
+            f,
+            prefix: String::new(),
+            depth: 0,
+            visiting: HashSet::new(),
+            seen: HashSet::new(),
+            truncated: false,
+            result: Ok(()),
+        };
+
+        self.value.visit(&mut printer);
+
+        printer.result
+    }
+}
+
+struct Printer<'t, 'a, 'f, 'g> {
+    tree: &'t DebugTree<'a>,
+    f: &'f mut Formatter<'g>,
+    // The guides of the ancestors, `│   ` or blanks
+    prefix: String,
+    depth: usize,
+    // The shared values being written, i.e. the ancestors of the current one
+    visiting: HashSet<*const ()>,
+    // The shared values written in full
+    seen: HashSet<*const ()>,
+    // Whether a node was cut off by the limits since it was last reset
+    truncated: bool,
+    // The first error, nothing is written after it
+    result: fmt::Result,
+}
+
+impl Printer<'_, '_, '_, '_> {
+    fn write(&mut self, args: fmt::Arguments) {
+        if self.result.is_ok() {
+            self.result = self.f.write_fmt(args);
+        }
+    }
+
+    // The guides, `write` would borrow all of `self`
+    fn write_prefix(&mut self) {
+        if self.result.is_ok() {
+            self.result = self.f.write_str(&self.prefix);
+        }
+    }
+
+    fn child(&mut self, label: Label, value: &dyn Visit, last: bool) {
+        let (branch, guide) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        self.write_prefix();
+        self.write(format_args!("{}", branch));
+        match label {
+            Label::Field(name) => self.write(format_args!("{}: ", name)),
+            Label::Index(index) => self.write(format_args!("[{}]: ", index)),
+            Label::Key(key) => self.write(format_args!("{:?}: ", key)),
+        }
+
+        self.prefix.push_str(guide);
+        self.depth += 1;
+        value.visit(self);
+        self.depth -= 1;
+        self.prefix.truncate(self.prefix.len() - guide.len());
+    }
+}
+
+impl Visitor for Printer<'_, '_, '_, '_> {
+    fn leaf(&mut self, value: &dyn Debug) {
+        self.write(format_args!("{:?}\n", value));
+    }
+
+    fn node(&mut self, name: &str, children: &[(Label, &dyn Visit)]) {
+        let tree = self.tree;
+        let children: Vec<_> = children
+            .iter()
+            .filter(|(label, _)| match (label, &tree.filter) {
+                (Label::Field(field), Some(filter)) => filter(field),
+                _ => true,
+            })
+            .collect();
+
+        if children.is_empty() {
+            self.write(format_args!("{}\n", name));
+            return;
+        }
+        if self.depth >= tree.max_depth {
+            self.write(format_args!("{} …\n", name));
+            self.truncated = true;
+            return;
+        }
+
+        self.write(format_args!("{}\n", name));
+
+        let shown = children.len().min(tree.max_children);
+        let hidden = children.len() - shown;
+
+        for (i, (label, value)) in children.iter().take(shown).enumerate() {
+            self.child(*label, *value, i + 1 == shown && hidden == 0);
+        }
+
+        if hidden > 0 {
+            self.truncated = true;
+            self.write_prefix();
+            self.write(format_args!("└── … {} more\n", hidden));
+        }
+    }
+
+    fn shared(&mut self, ptr: *const (), value: &dyn Visit) {
+        if self.seen.contains(&ptr) || !self.visiting.insert(ptr) {
+            self.write(format_args!("Rc (seen)\n"));
+            return;
+        }
+
+        let outer_truncated = std::mem::replace(&mut self.truncated, false);
+        value.visit(self);
+        self.visiting.remove(&ptr);
+
+        // Only mark it once it's in the output, not when it was cut off
+        if !self.truncated {
+            self.seen.insert(ptr);
+        }
+        self.truncated |= outer_truncated;
+    }
+}
+
+macro_rules! impl_visit_leaf {
+    ($($t:ty),*) => {
+        $(
+            impl Visit for $t {
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    // `&self` for `str`, which isn't `Sized`
+                    visitor.leaf(&self);
+                }
+            }
+        )*
+    };
+}
+
+impl_visit_leaf!(
+    i8, i16, i32, i64, u8, u16, u32, u64, usize, isize, f32, f64, bool, char, str, String
+);
+
+impl<T: Visit + ?Sized> Visit for &T {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        (**self).visit(visitor);
+    }
+}
+
+impl<T: Visit + ?Sized> Visit for Box<T> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        (**self).visit(visitor);
+    }
+}
+
+impl<T: Visit> Visit for Rc<T> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        visitor.shared(Rc::as_ptr(self) as *const (), &**self);
+    }
+}
+
+impl<T: Visit> Visit for RefCell<T> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        match self.try_borrow() {
+            Ok(value) => value.visit(visitor),
+            Err(_) => visitor.leaf(&format_args!("<borrowed>")),
+        }
+    }
+}
+
+impl<T: Visit> Visit for Option<T> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        match self {
+            Some(value) => visitor.node("Some", &[(Label::Index(0), value)]),
+            None => visitor.leaf(&format_args!("None")),
+        }
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        let children: Vec<(Label, &dyn Visit)> = self
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (Label::Index(i), value as &dyn Visit))
+            .collect();
+
+        visitor.node("Vec", &children);
+    }
+}
+
+// NOTE: the entries are sorted by the `Debug` of their keys, so the output
+//       doesn't change between runs
+impl<K: Debug, V: Visit, S> Visit for HashMap<K, V, S> {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        let mut entries: Vec<_> = self
+            .iter()
+            .map(|(k, v)| (format!("{:?}", k), k, v))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let children: Vec<(Label, &dyn Visit)> = entries
+            .iter()
+            .map(|(_, k, v)| (Label::Key(*k), *v as &dyn Visit))
+            .collect();
+
+        visitor.node("HashMap", &children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Node {
+        name: &'static str,
+        children: RefCell<Vec<Rc<Node>>>,
+    }
+
+    impl Visit for Node {
+        fn visit(&self, visitor: &mut dyn Visitor) {
+            visitor.node(
+                "Node",
+                &[
+                    (Label::Field("name"), &self.name),
+                    (Label::Field("children"), &self.children),
+                ],
+            );
+        }
+    }
+
+    fn node(name: &'static str) -> Rc<Node> {
+        Rc::new(Node {
+            name,
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    #[test]
+    fn test_tree() {
+        let v = vec![vec![1, 2], vec![], vec![3]];
+
+        assert_eq!(
+            DebugTree::new(&v).to_string(),
+            "\
+Vec
+├── [0]: Vec
+│   ├── [0]: 1
+│   └── [1]: 2
+├── [1]: Vec
+└── [2]: Vec
+    └── [0]: 3
+"
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let v = vec![vec![1, 2], vec![3]];
+
+        assert_eq!(
+            DebugTree::new(&v).max_depth(1).to_string(),
+            "Vec\n├── [0]: Vec …\n└── [1]: Vec …\n"
+        );
+        assert_eq!(
+            DebugTree::new(&(0..10).collect::<Vec<_>>())
+                .max_children(2)
+                .to_string(),
+            "Vec\n├── [0]: 0\n├── [1]: 1\n└── … 8 more\n"
+        );
+        assert_eq!(DebugTree::new(&v).max_depth(0).to_string(), "Vec …\n");
+    }
+
+    #[test]
+    fn test_hash_map_is_sorted() {
+        let map: HashMap<_, _> = vec![("b", 2), ("a", 1), ("c", 3)].into_iter().collect();
+
+        assert_eq!(
+            DebugTree::new(&map).to_string(),
+            "HashMap\n├── \"a\": 1\n├── \"b\": 2\n└── \"c\": 3\n"
+        );
+    }
+
+    #[test]
+    fn test_cycle_and_filter() {
+        let a = node("a");
+        let b = node("b");
+        a.children.borrow_mut().push(b.clone());
+        b.children.borrow_mut().push(a.clone());
+
+        assert_eq!(
+            DebugTree::new(&a).to_string(),
+            "\
+Node
+├── name: \"a\"
+└── children: Vec
+    └── [0]: Node
+        ├── name: \"b\"
+        └── children: Vec
+            └── [0]: Rc (seen)
+"
+        );
+        assert_eq!(
+            DebugTree::new(&a)
+                .filter_fields(|field| field != "name")
+                .to_string(),
+            "\
+Node
+└── children: Vec
+    └── [0]: Node
+        └── children: Vec
+            └── [0]: Rc (seen)
+"
+        );
+
+        // Break the cycle, so both nodes are dropped
+        b.children.borrow_mut().clear();
+    }
+
+    struct Reachable {
+        deep: Vec<Rc<Node>>,
+        shallow: Rc<Node>,
+        again: Rc<Node>,
+    }
+
+    impl Visit for Reachable {
+        fn visit(&self, visitor: &mut dyn Visitor) {
+            visitor.node(
+                "Reachable",
+                &[
+                    (Label::Field("deep"), &self.deep),
+                    (Label::Field("shallow"), &self.shallow),
+                    (Label::Field("again"), &self.again),
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_first_visit() {
+        let shared = node("s");
+        let reachable = Reachable {
+            deep: vec![shared.clone()],
+            shallow: shared.clone(),
+            again: shared,
+        };
+
+        // Cut off by the depth, then written in full, then seen
+        assert_eq!(
+            DebugTree::new(&reachable).max_depth(2).to_string(),
+            "\
+Reachable
+├── deep: Vec
+│   └── [0]: Node …
+├── shallow: Node
+│   ├── name: \"s\"
+│   └── children: Vec
+└── again: Rc (seen)
+"
+        );
+    }
+}
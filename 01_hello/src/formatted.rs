@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 1.2.2.1 `List` display generalized for any iterable, e.g.
+//         Formatted::new(&v).indices(true)  =>  [0: 1, 1: 2, 2: 3]
+//       The formatter's flags are passed on to the elements, so `{:.2}` or
+//       `{:>5}` apply to each element, and `{:#}` prints one element per line
+//       Nested collections are written by nesting `Formatted` values, in
+//       multiline mode each level indents the lines of its elements
+//       In multiline mode the elements are written through `Indented`
+//       with the flags copied, a fill other than a space is lost there, e.g.
+//       `{:*^#5}` pads with spaces
+
+use std::fmt::{self, Alignment, Display, Formatter, Write};
+
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Formatted<I> {
+    items: I,
+    open: &'static str,
+    close: &'static str,
+    separator: &'static str,
+    indices: bool,
+    max_items: Option<usize>,
+}
+
+impl<I> Formatted<I> {
+    // `[1, 2, 3]` by default
+    pub fn new(items: I) -> Formatted<I> {
+        Formatted {
+            items,
+            open: "[",
+            close: "]",
+            separator: ", ",
+            indices: false,
+            max_items: None,
+        }
+    }
+
+    pub fn delimiters(mut self, open: &'static str, close: &'static str) -> Self {
+        self.open = open;
+        self.close = close;
+        self
+    }
+
+    // Trailing whitespace is dropped in multiline mode
+    pub fn separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    // Prefix each element with its index: `0: 1`
+    pub fn indices(mut self, indices: bool) -> Self {
+        self.indices = indices;
+        self
+    }
+
+    // Write at most `max_items` elements, followed by `…` if there are more
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+impl<I> Display for Formatted<I>
+where
+    I: Clone + IntoIterator,
+    I::Item: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let max_items = self.max_items.unwrap_or(usize::MAX);
+        let mut items = self.items.clone().into_iter().enumerate().peekable();
+
+        f.write_str(self.open)?;
+
+        if !f.alternate() {
+            while let Some((index, item)) = items.next() {
+                if index == max_items {
+                    f.write_str("…")?;
+                    break;
+                }
+
+                if self.indices {
+                    write!(f, "{}: ", index)?;
+                }
+
+                // Forward `f` itself to keep the flags
+                item.fmt(f)?;
+
+                if items.peek().is_some() {
+                    f.write_str(self.separator)?;
+                }
+            }
+
+            return f.write_str(self.close);
+        }
+
+        let flags = Flags::of(f);
+        let separator = self.separator.trim_end();
+        let is_empty = items.peek().is_none();
+
+        for (index, item) in items {
+            writeln!(f)?;
+
+            let mut line = Indented::new(f);
+            if index == max_items {
+                line.write_str("…")?;
+                break;
+            }
+
+            if self.indices {
+                write!(line, "{}: ", index)?;
+            }
+
+            flags.write(&mut line, &item)?;
+            line.write_str(separator)?;
+        }
+
+        if !is_empty {
+            writeln!(f)?;
+        }
+
+        f.write_str(self.close)
+    }
+}
+
+// Indents every line written through it, also the ones of nested
+// `Formatted` values, which then indent their elements once more
+struct Indented<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    line_start: bool,
+}
+
+impl<'a, 'b> Indented<'a, 'b> {
+    fn new(f: &'a mut Formatter<'b>) -> Self {
+        Indented {
+            f,
+            line_start: true,
+        }
+    }
+}
+
+impl Write for Indented<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for line in s.split_inclusive('\n') {
+            if self.line_start && line != "\n" {
+                self.f.write_str(INDENT)?;
+            }
+
+            self.f.write_str(line)?;
+            self.line_start = line.ends_with('\n');
+        }
+
+        Ok(())
+    }
+}
+
+// The flags of a multiline spec, without the fill, `#` is always set
+#[derive(Clone, Copy)]
+struct Flags {
+    align: Option<Alignment>,
+    plus: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+// `write!` only takes the flags in the literal, so each combination is
+// spelled out, `$spec` gathers the literal from left to right
+macro_rules! write_flags {
+    (@align $w:ident, $item:ident, $flags:ident) => {
+        match $flags.align {
+            None => write_flags!(@sign $w, $item, $flags, []),
+            Some(Alignment::Left) => write_flags!(@sign $w, $item, $flags, ["<"]),
+            Some(Alignment::Right) => write_flags!(@sign $w, $item, $flags, [">"]),
+            Some(Alignment::Center) => write_flags!(@sign $w, $item, $flags, ["^"]),
+        }
+    };
+    (@sign $w:ident, $item:ident, $flags:ident, [$($spec:literal)*]) => {
+        if $flags.plus {
+            write_flags!(@zero $w, $item, $flags, [$($spec)* "+"])
+        } else {
+            write_flags!(@zero $w, $item, $flags, [$($spec)*])
+        }
+    };
+    (@zero $w:ident, $item:ident, $flags:ident, [$($spec:literal)*]) => {
+        if $flags.zero {
+            write_flags!(@precision $w, $item, $flags, [$($spec)* "#0"])
+        } else {
+            write_flags!(@precision $w, $item, $flags, [$($spec)* "#"])
+        }
+    };
+    (@precision $w:ident, $item:ident, $flags:ident, [$($spec:literal)*]) => {
+        match $flags.precision {
+            Some(precision) => write!(
+                $w,
+                concat!("{:", $($spec,)* "width$.precision$}"),
+                $item,
+                width = $flags.width,
+                precision = precision
+            ),
+            None => write!(
+                $w,
+                concat!("{:", $($spec,)* "width$}"),
+                $item,
+                width = $flags.width
+            ),
+        }
+    };
+}
+
+impl Flags {
+    fn of(f: &Formatter) -> Flags {
+        Flags {
+            align: f.align(),
+            plus: f.sign_plus(),
+            zero: f.sign_aware_zero_pad(),
+            // No width and a width of 0 pad the same
+            width: f.width().unwrap_or(0),
+            precision: f.precision(),
+        }
+    }
+
+    fn write<W: Write, T: Display>(self, w: &mut W, item: &T) -> fmt::Result {
+        let flags = self;
+
+        write_flags!(@align w, item, flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_and_indices() {
+        let v = vec![1, 2, 3];
+
+        assert_eq!(Formatted::new(&v).to_string(), "[1, 2, 3]");
+        assert_eq!(
+            Formatted::new(&v).indices(true).to_string(),
+            "[0: 1, 1: 2, 2: 3]"
+        );
+        assert_eq!(Formatted::new(&Vec::<i32>::new()).to_string(), "[]");
+        assert_eq!(format!("{:#}", Formatted::new(&Vec::<i32>::new())), "[]");
+    }
+
+    #[test]
+    fn test_options() {
+        let v = [1, 2, 3, 4, 5];
+
+        assert_eq!(
+            Formatted::new(&v)
+                .delimiters("{", "}")
+                .separator("; ")
+                .to_string(),
+            "{1; 2; 3; 4; 5}"
+        );
+        assert_eq!(Formatted::new(&v).max_items(2).to_string(), "[1, 2, …]");
+        assert_eq!(
+            Formatted::new(&v).max_items(5).to_string(),
+            "[1, 2, 3, 4, 5]"
+        );
+        assert_eq!(Formatted::new(&v).max_items(0).to_string(), "[…]");
+    }
+
+    #[test]
+    fn test_element_flags() {
+        let v = [1.0, 2.5, 1.0 / 3.0];
+
+        assert_eq!(format!("{:.2}", Formatted::new(&v)), "[1.00, 2.50, 0.33]");
+        assert_eq!(
+            format!("{:>4}", Formatted::new(&["a", "bc"])),
+            "[   a,   bc]"
+        );
+    }
+
+    #[test]
+    fn test_multiline() {
+        let v = [1, 2, 3];
+
+        assert_eq!(
+            format!("{:#}", Formatted::new(&v).indices(true).max_items(2)),
+            "[\n    0: 1,\n    1: 2,\n    …\n]"
+        );
+    }
+
+    #[test]
+    fn test_multiline_flags() {
+        assert_eq!(
+            format!("{:#.1}", Formatted::new(&[1.0, -2.25])),
+            "[\n    1.0,\n    -2.2,\n]"
+        );
+        assert_eq!(
+            format!("{:+#05}", Formatted::new(&[1, -2])),
+            "[\n    +0001,\n    -0002,\n]"
+        );
+        assert_eq!(
+            format!("{:*^#5}", Formatted::new(&["a"])),
+            "[\n      a  ,\n]"
+        );
+    }
+
+    // Elements that write their own lines, or fail
+    struct Lines<'a>(&'a str);
+
+    impl Display for Lines<'_> {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            if self.0.is_empty() {
+                return Err(fmt::Error);
+            }
+
+            // An unrelated `Formatted` is not indented by the outer one
+            let inner = Formatted::new(self.0.split(' ')).separator(" ");
+            write!(f, "{}\n{}", inner, inner)
+        }
+    }
+
+    #[test]
+    fn test_element_lines_and_errors() {
+        assert_eq!(
+            format!("{:#}", Formatted::new(&[Lines("a b")])),
+            "[\n    [a b]\n    [a b],\n]"
+        );
+
+        let mut s = String::new();
+        assert!(write!(s, "{:#}", Formatted::new(&[Lines("")])).is_err());
+        // A failed element leaves nothing behind
+        assert_eq!(
+            format!("{:#}", Formatted::new([[1]].iter().map(Formatted::new))),
+            "[\n    [\n        1,\n    ],\n]"
+        );
+        assert_eq!(Formatted::new(&[Lines("c")]).to_string(), "[[c]\n[c]]");
+    }
+
+    #[test]
+    fn test_nested() {
+        let v = [vec![1, 2], vec![], vec![3]];
+        let nested = Formatted::new(v.iter().map(Formatted::new));
+
+        assert_eq!(nested.to_string(), "[[1, 2], [], [3]]");
+        assert_eq!(
+            format!("{:#}", nested),
+            "[\n    [\n        1,\n        2,\n    ],\n    [],\n    [\n        3,\n    ],\n]"
+        );
+    }
+}
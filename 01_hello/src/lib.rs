@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the modules of the chapter that the binaries in `src/bin` use too
+
+pub mod city;
+pub mod complex;
+pub mod template;
@@ -11,6 +11,13 @@
 
 //! Generate library docs for the enclosing item.
 
+mod color;
+mod debug_tree;
+mod formatted;
+
+// NOTE: in `lib.rs`, shared with `src/bin`
+use _1_hello::{city, complex, template};
+
 /// Generate library docs for the following item.
 fn main() {
     // https://doc.rust-lang.org/rust-by-example/hello.html
@@ -150,6 +157,93 @@ fn main() {
         // The problem with `derive` is there is no control over how
         // the results look. What if I want this to just show a `7`?
         println!("Now {:?} will print!", Deep(Structure(7)));
+
+        // NOTE: the pretty printing example of 1.2.1
+        #[derive(Debug)]
+        struct Person<'a> {
+            name: &'a str,
+            age: u8,
+        }
+
+        let name = "Peter";
+        let age = 27;
+        let peter = Person { name, age };
+
+        // Pretty print
+        println!("{:#?}", peter);
+
+        // NOTE: `{:#?}` writes everything, `DebugTree` can be bounded
+        {
+            use debug_tree::{DebugTree, Label, Visit, Visitor};
+            use std::cell::RefCell;
+            use std::collections::HashMap;
+            use std::rc::Rc;
+
+            impl Visit for Structure {
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    visitor.node("Structure", &[(Label::Field("0"), &self.0)]);
+                }
+            }
+
+            impl Visit for Deep {
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    visitor.node("Deep", &[(Label::Field("0"), &self.0)]);
+                }
+            }
+
+            impl Visit for Person<'_> {
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    visitor.node(
+                        "Person",
+                        &[
+                            (Label::Field("name"), &self.name),
+                            (Label::Field("age"), &self.age),
+                        ],
+                    );
+                }
+            }
+
+            // A person who knows people, who may know them back
+            struct Friend {
+                person: Person<'static>,
+                friends: RefCell<Vec<Rc<Friend>>>,
+            }
+
+            impl Visit for Friend {
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    visitor.node(
+                        "Friend",
+                        &[
+                            (Label::Field("person"), &self.person),
+                            (Label::Field("friends"), &self.friends),
+                        ],
+                    );
+                }
+            }
+
+            print!("{}", DebugTree::new(&Deep(Structure(7))));
+            print!("{}", DebugTree::new(&Deep(Structure(7))).max_depth(1));
+            print!("{}", DebugTree::new(&peter).filter_fields(|f| f != "age"));
+
+            let friend = |name, age| {
+                Rc::new(Friend {
+                    person: Person { name, age },
+                    friends: RefCell::new(Vec::new()),
+                })
+            };
+            let alice = friend("Alice", 31);
+            let bob = friend("Bob", 29);
+            alice.friends.borrow_mut().push(bob.clone());
+            bob.friends.borrow_mut().push(alice.clone());
+            print!("{}", DebugTree::new(&alice).filter_fields(|f| f != "age"));
+            // Break the cycle, so that both can be dropped
+            bob.friends.borrow_mut().clear();
+
+            let ages: HashMap<_, _> = (1..=6)
+                .map(|age| (format!("child {}", age), vec![age; age as usize]))
+                .collect();
+            print!("{}", DebugTree::new(&ages).max_children(3).max_depth(1));
+        }
     }
 
     // https://doc.rust-lang.org/rust-by-example/hello/print/print_display.html
@@ -237,25 +331,64 @@ fn main() {
         //            > Display: 3.3 + 7.2i
         //            > Debug: Complex { real: 3.3, imag: 7.2 }
 
-        #[derive(Debug)]
-        struct Complex {
-            real: f64,
-            imaq: f64,
-        }
-
-        impl fmt::Display for Complex {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "{} + {}i", self.real, self.imaq)
-            }
-        }
+        // NOTE: `Complex` moved to `complex.rs` and became generic
+        use complex::Complex;
 
         let complex_number = Complex {
             real: 3.3,
-            imaq: 7.2,
+            imag: 7.2,
         };
 
         println!("Display: {}", complex_number);
         println!("Debug: {:?}", complex_number);
+
+        let other = Complex::new(1.0, -2.0);
+        let (r, theta) = complex_number.to_polar();
+
+        println!();
+        println!(
+            "({}) + ({}) = {}",
+            complex_number,
+            other,
+            complex_number + other
+        );
+        println!(
+            "({}) - ({}) = {}",
+            complex_number,
+            other,
+            complex_number - other
+        );
+        println!(
+            "({}) * ({}) = {:.2}",
+            complex_number,
+            other,
+            complex_number * other
+        );
+        println!(
+            "({}) / ({}) = {:.2}",
+            complex_number,
+            other,
+            complex_number / other
+        );
+        println!("-({}) = {}", other, -other);
+        println!("conj({}) = {}", other, other.conj());
+        println!("|{}| = {:.3}, arg = {:.3}", complex_number, r, theta);
+        println!(
+            "from_polar({:.3}, {:.3}) = {:.2}",
+            r,
+            theta,
+            Complex::from_polar(r, theta)
+        );
+        println!(
+            "exp(i pi) = {:.3}",
+            (Complex::I * std::f64::consts::PI.into()).exp()
+        );
+        println!("ln({}) = {:.3}", other, other.ln());
+        println!("sqrt(-1) = {}", Complex::from(-1.0).sqrt());
+        println!("i^i = {:.5}", Complex::<f64>::I.pow(Complex::I));
+        println!("sin({}) = {:.3}", other, other.sin());
+        println!("cos({}) = {:.3}", other, other.cos());
+        println!("|{}|^2 = {}", other, other.norm_sqr());
     }
 
     // https://doc.rust-lang.org/rust-by-example/hello/print/print_display/testcase_list.html
@@ -305,28 +438,41 @@ fn main() {
         {
             use std::fmt;
 
+            use formatted::Formatted;
+
             struct List(Vec<i32>);
 
+            // NOTE: the loop moved to `Formatted`, which takes any iterable
             impl fmt::Display for List {
                 fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                    let vec = &self.0;
-
-                    write!(f, "[")?;
-
-                    for (index, value) in vec.iter().enumerate() {
-                        if index != 0 {
-                            write!(f, ", ")?;
-                        }
-                        write!(f, "{}: {}", index, value)?;
-                    }
-
-                    write!(f, "]")
+                    fmt::Display::fmt(&Formatted::new(&self.0).indices(true), f)
                 }
             }
 
             let v = List(vec![1, 2, 3]);
 
             println!("{}", v);
+
+            // Options
+            let floats = [1.0, 2.5, 1.0 / 3.0, 4.25];
+            let matrix = [vec![1, 2, 3], vec![], vec![4, 5]];
+
+            println!("{:.2}", Formatted::new(&floats));
+            println!("{:>6.1}", Formatted::new(&floats).max_items(2));
+            println!(
+                "{}",
+                Formatted::new(&floats).delimiters("(", ")").separator(" ")
+            );
+            println!("{:#}", v);
+            println!("{}", Formatted::new(matrix.iter().map(Formatted::new)));
+            println!(
+                "{:#}",
+                Formatted::new(
+                    matrix
+                        .iter()
+                        .map(|row| Formatted::new(row).delimiters("(", ")"))
+                )
+            );
         }
     }
 
@@ -335,35 +481,7 @@ fn main() {
     println!("--- 1.2.3. Formatting ---");
     {
         {
-            use std::fmt::{self, Display, Formatter};
-
-            struct City {
-                name: &'static str,
-                // Latitude
-                lat: f32,
-                // Longitude
-                lon: f32,
-            }
-
-            impl Display for City {
-                // `f` is a buffer, and this method must write the formatted string into it
-                fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                    let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-                    let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
-
-                    // `write!` is like `format!`, but it will write the formatted string
-                    // into a buffer (the first argument)
-                    write!(
-                        f,
-                        "{}: {:.3}°{} {:.3}°{}",
-                        self.name,
-                        self.lat.abs(),
-                        lat_c,
-                        self.lon.abs(),
-                        lon_c
-                    )
-                }
-            }
+            // NOTE: `City` with its `Display` moved to `city.rs`
 
             #[derive(Debug)]
             struct Color {
@@ -372,25 +490,7 @@ fn main() {
                 blue: u8,
             }
 
-            for city in [
-                City {
-                    name: "Dublin",
-                    lat: 53.347778,
-                    lon: -6.259722,
-                },
-                City {
-                    name: "Oslo",
-                    lat: 59.95,
-                    lon: 10.75,
-                },
-                City {
-                    name: "Vancouver",
-                    lat: 49.25,
-                    lon: -123.1,
-                },
-            ]
-            .iter()
-            {
+            for city in city::sample_cities().iter() {
                 println!("{}", *city);
             }
             for color in [
@@ -417,6 +517,42 @@ fn main() {
                 println!("{:?}", *color);
             }
         }
+        {
+            // NOTE: coordinates can be read back, in degrees, minutes and
+            //       seconds too
+            use city::{City, Coordinate};
+
+            let [dublin, oslo, vancouver] = city::sample_cities();
+
+            println!("{:#}", dublin);
+            for s in &[
+                "53.348°N 6.260°W",
+                "53°20'52\"N 6°15'35\"W",
+                "59.95, 10.75",
+                "49°15′N 123°6′W",
+                "91°N 0°E",
+                "53°60'N 6°W",
+            ] {
+                match s.parse::<Coordinate>() {
+                    Ok(coordinate) => println!("{:?} is {} ({:#})", s, coordinate, coordinate),
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
+
+            let bearing = |from: &City, to: &City| {
+                println!(
+                    "{} -> {}: {:.0} km, initial bearing {:.1}°",
+                    from.name,
+                    to.name,
+                    from.distance_km(to),
+                    from.bearing(to)
+                )
+            };
+            bearing(&dublin, &oslo);
+            bearing(&oslo, &vancouver);
+            bearing(&vancouver, &dublin);
+            // NOTE: the full matrix is printed by `cargo run --bin city_distances`
+        }
         println!();
         println!("--- Activity ---");
         //        [x] Add an implementation of the fmt::Display trait for the Color struct
@@ -425,26 +561,8 @@ fn main() {
         //            > RGB (0, 3, 254) 0x0003FE
         //            > RGB (0, 0, 0) 0x000000
         {
-            use std::fmt::{self, Display, Formatter};
-
-            #[derive(Debug)]
-            struct Color {
-                red: u8,
-                green: u8,
-                blue: u8,
-            }
-
-            impl Display for Color {
-                fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                    write!(
-                        f,
-                        "RGB ({r}, {g}, {b}) 0x{r:02X}{g:02X}{b:02X}",
-                        r = self.red,
-                        g = self.green,
-                        b = self.blue,
-                    )
-                }
-            }
+            // NOTE: `Color` with its `Display` moved to `color.rs`
+            use color::Color;
 
             let colors = [
                 Color {
@@ -467,6 +585,79 @@ fn main() {
             for color in colors.iter() {
                 println!("{}", *color);
             }
+
+            // NOTE: and back, with other notations too
+            for s in &[
+                "RGB (128, 255, 90) 0x80FF5A",
+                "#0003FE",
+                "#FFF",
+                "rebeccapurple",
+                "RGB (1, 2, 3) 0x000000",
+                "ultraviolet",
+            ] {
+                match s.parse::<Color>() {
+                    Ok(color) => println!("{:?} is {}", s, color),
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
+
+            let [light, dark, black] = colors;
+            let hsl = light.to_hsl();
+            let hsv = dark.to_hsv();
+            println!(
+                "{} is HSL ({:.1}°, {:.3}, {:.3})",
+                light, hsl.hue, hsl.saturation, hsl.lightness
+            );
+            println!(
+                "{} is HSV ({:.1}°, {:.3}, {:.3})",
+                dark, hsv.hue, hsv.saturation, hsv.value
+            );
+            println!(
+                "and back: {} {}",
+                Color::from_hsl(hsl),
+                Color::from_hsv(hsv)
+            );
+            println!("half of it over black: {}", black.blend(light, 0.5));
+            println!(
+                "contrast ratio with black: {:.2}:1 and {:.2}:1 (0x{:06X})",
+                light.contrast_ratio(&black),
+                dark.contrast_ratio(&black),
+                dark.hex()
+            );
+        }
+        {
+            // NOTE: the same formats parsed at runtime, so reports can be
+            //       changed without recompiling
+            use city::City;
+            use color::Color;
+            use template::Template;
+
+            let color = Color::from_hex(0x80FF5A);
+            let [dublin, ..] = city::sample_cities();
+
+            for (template, values) in &[
+                (Color::DISPLAY_TEMPLATE, color.template_values()),
+                ("{hex:#08x} {hex:>12b}", color.template_values()),
+                (City::DISPLAY_TEMPLATE, dublin.template_values()),
+                (
+                    "{name:-^13}|{lat:+9.2e}|{lon:>8.1}",
+                    dublin.template_values(),
+                ),
+                ("{name:x}", dublin.template_values()),
+                ("{name} {population}", dublin.template_values()),
+                ("{name", dublin.template_values()),
+            ] {
+                match template.parse::<Template>() {
+                    Ok(template) => match template.render(values) {
+                        Ok(s) => println!("{}", s),
+                        Err(e) => println!("Error: {}", e),
+                    },
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
+
+            let template = Template::parse(City::DISPLAY_TEMPLATE).unwrap();
+            println!("arguments: {:?}", template.names().collect::<Vec<_>>());
         }
     }
 }
@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: a runtime counterpart of `format!` with named arguments, the
+//       template is parsed once and rendered against a map of values:
+//         Template::parse("0x{r:02X}")?.render(&values)
+//       Supported: `{{` and `}}` escapes, and after the `:`
+//         [[fill]align][+][#][0][width][.precision][type]
+//       with align `<`, `^`, `>` and type one of `x`, `X`, `o`, `b`, `e`, `E`
+//       Widths and precisions are literal numbers, `name$` is not supported
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    // `bits` is the width of the source type, negative values print in hex,
+    // octal and binary as two's complement of that width, as `format!` does
+    Int { value: i64, bits: u32 },
+    Float(f64),
+    Str(String),
+}
+
+macro_rules! impl_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(value: $t) -> Self {
+                    Value::Int {
+                        value: value.into(),
+                        bits: <$t>::BITS,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Str(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Value::Str(value.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Display,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    LowerExp,
+    UpperExp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    ty: Type,
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Spec {
+            fill: ' ',
+            align: None,
+            plus: false,
+            alternate: false,
+            zero: false,
+            width: 0,
+            precision: None,
+            ty: Type::Display,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Argument { name: String, spec: Spec },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+// Byte offsets point into the template string
+#[derive(Debug, PartialEq)]
+pub enum ParseTemplateError {
+    // A `{` without `}`, or a `}` that is neither closing nor escaped
+    UnmatchedBrace(usize),
+    // `{}` and `{:x}`, positional arguments don't make sense here
+    MissingName(usize),
+    InvalidSpec { offset: usize, spec: String },
+}
+
+impl Display for ParseTemplateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseTemplateError::UnmatchedBrace(offset) => {
+                write!(f, "unmatched brace at offset {}", offset)
+            }
+            ParseTemplateError::MissingName(offset) => {
+                write!(f, "missing argument name at offset {}", offset)
+            }
+            ParseTemplateError::InvalidSpec { offset, spec } => {
+                write!(f, "invalid format spec `{}` at offset {}", spec, offset)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseTemplateError {}
+
+#[derive(Debug, PartialEq)]
+pub enum RenderError {
+    MissingValue(String),
+    // e.g. `{name:x}` with a string value
+    TypeMismatch { name: String, spec: char },
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RenderError::MissingValue(name) => write!(f, "no value for `{}`", name),
+            RenderError::TypeMismatch { name, spec } => {
+                write!(f, "`{}` can't be formatted with `{}`", name, spec)
+            }
+        }
+    }
+}
+
+impl error::Error for RenderError {}
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Template, ParseTemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.char_indices().peekable();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(ParseTemplateError::UnmatchedBrace(offset)),
+                '{' => {
+                    let end = match s[offset..].find('}') {
+                        Some(end) => offset + end,
+                        None => return Err(ParseTemplateError::UnmatchedBrace(offset)),
+                    };
+                    let argument = &s[offset + 1..end];
+
+                    let (name, spec) = match argument.find(':') {
+                        Some(colon) => (&argument[..colon], Some(&argument[colon + 1..])),
+                        None => (argument, None),
+                    };
+                    let name = name.trim();
+
+                    if name.is_empty() {
+                        return Err(ParseTemplateError::MissingName(offset));
+                    }
+
+                    let spec = match spec {
+                        Some(spec) => {
+                            parse_spec(spec).ok_or_else(|| ParseTemplateError::InvalidSpec {
+                                offset,
+                                spec: spec.to_owned(),
+                            })?
+                        }
+                        None => Spec::default(),
+                    };
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.split_off(0)));
+                    }
+                    segments.push(Segment::Argument {
+                        name: name.to_owned(),
+                        spec,
+                    });
+
+                    while let Some(&(i, _)) = chars.peek() {
+                        chars.next();
+                        if i == end {
+                            break;
+                        }
+                    }
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Template { segments })
+    }
+
+    // The names of the arguments in order of appearance, with repetitions
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Argument { name, .. } => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    pub fn render<K>(&self, values: &HashMap<K, Value>) -> Result<String, RenderError>
+    where
+        K: Borrow<str> + Hash + Eq,
+    {
+        let mut out = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => out.push_str(literal),
+                Segment::Argument { name, spec } => {
+                    let value = values
+                        .get(name.as_str())
+                        .ok_or_else(|| RenderError::MissingValue(name.clone()))?;
+
+                    render_value(&mut out, name, value, spec)?;
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl FromStr for Template {
+    type Err = ParseTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::parse(s)
+    }
+}
+
+fn parse_spec(s: &str) -> Option<Spec> {
+    let mut spec = Spec::default();
+    let chars: Vec<char> = s.chars().collect();
+    let mut i = 0;
+
+    let align = |c: char| match c {
+        '<' => Some(Align::Left),
+        '^' => Some(Align::Center),
+        '>' => Some(Align::Right),
+        _ => None,
+    };
+
+    // The fill needs an align after it
+    if let Some(a) = chars.get(1).copied().and_then(align) {
+        spec.fill = chars[0];
+        spec.align = Some(a);
+        i = 2;
+    } else if let Some(a) = chars.first().copied().and_then(align) {
+        spec.align = Some(a);
+        i = 1;
+    }
+
+    if chars.get(i) == Some(&'+') {
+        spec.plus = true;
+        i += 1;
+    }
+    if chars.get(i) == Some(&'#') {
+        spec.alternate = true;
+        i += 1;
+    }
+    if chars.get(i) == Some(&'0') {
+        spec.zero = true;
+        i += 1;
+    }
+
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    let width = digits(&mut i);
+    if !width.is_empty() {
+        spec.width = width.parse().ok()?;
+    }
+
+    if chars.get(i) == Some(&'.') {
+        i += 1;
+        spec.precision = Some(digits(&mut i).parse().ok()?);
+    }
+
+    spec.ty = match chars.get(i) {
+        None => return Some(spec),
+        Some('x') => Type::LowerHex,
+        Some('X') => Type::UpperHex,
+        Some('o') => Type::Octal,
+        Some('b') => Type::Binary,
+        Some('e') => Type::LowerExp,
+        Some('E') => Type::UpperExp,
+        Some(_) => return None,
+    };
+
+    if i + 1 == chars.len() {
+        Some(spec)
+    } else {
+        None
+    }
+}
+
+fn render_value(
+    out: &mut String,
+    name: &str,
+    value: &Value,
+    spec: &Spec,
+) -> Result<(), RenderError> {
+    let mismatch = |spec: char| RenderError::TypeMismatch {
+        name: name.to_owned(),
+        spec,
+    };
+
+    // `sign` and `prefix` stay in front of the zero padding
+    let (sign, prefix, body, numeric) = match (value, spec.ty) {
+        (
+            &Value::Int { value, bits },
+            ty @ (Type::LowerHex | Type::UpperHex | Type::Octal | Type::Binary),
+        ) => {
+            let v = twos_complement(value, bits);
+            let (prefix, body) = match ty {
+                Type::LowerHex => ("0x", format!("{:x}", v)),
+                Type::UpperHex => ("0x", format!("{:X}", v)),
+                Type::Octal => ("0o", format!("{:o}", v)),
+                _ => ("0b", format!("{:b}", v)),
+            };
+
+            ("", prefix, body, true)
+        }
+        (Value::Int { value: v, .. }, ty) => {
+            let abs = v.unsigned_abs();
+            let body = match (ty, spec.precision) {
+                (Type::LowerExp, Some(p)) => format!("{:.*e}", p, abs),
+                (Type::LowerExp, None) => format!("{:e}", abs),
+                (Type::UpperExp, Some(p)) => format!("{:.*E}", p, abs),
+                (Type::UpperExp, None) => format!("{:E}", abs),
+                _ => abs.to_string(),
+            };
+
+            (sign(*v < 0, spec.plus), "", body, true)
+        }
+        (Value::Float(v), ty) => {
+            let abs = v.abs();
+            let body = match (ty, spec.precision) {
+                (Type::Display, Some(p)) => format!("{:.*}", p, abs),
+                (Type::Display, None) => abs.to_string(),
+                (Type::LowerExp, Some(p)) => format!("{:.*e}", p, abs),
+                (Type::LowerExp, None) => format!("{:e}", abs),
+                (Type::UpperExp, Some(p)) => format!("{:.*E}", p, abs),
+                (Type::UpperExp, None) => format!("{:E}", abs),
+                (ty, _) => return Err(mismatch(type_char(ty))),
+            };
+
+            (sign(v.is_sign_negative(), spec.plus), "", body, true)
+        }
+        (Value::Str(v), Type::Display) => {
+            // The precision truncates strings
+            let body = match spec.precision {
+                Some(p) => v.chars().take(p).collect(),
+                None => v.clone(),
+            };
+
+            ("", "", body, false)
+        }
+        (Value::Str(_), ty) => return Err(mismatch(type_char(ty))),
+    };
+
+    let prefix = if spec.alternate { prefix } else { "" };
+    let len = sign.chars().count() + prefix.len() + body.chars().count();
+    let padding = spec.width.saturating_sub(len);
+
+    if spec.zero && numeric {
+        out.push_str(sign);
+        out.push_str(prefix);
+        out.extend(std::iter::repeat_n('0', padding));
+        out.push_str(&body);
+
+        return Ok(());
+    }
+
+    let align = spec
+        .align
+        .unwrap_or(if numeric { Align::Right } else { Align::Left });
+    let (before, after) = match align {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+
+    out.extend(std::iter::repeat_n(spec.fill, before));
+    out.push_str(sign);
+    out.push_str(prefix);
+    out.push_str(&body);
+    out.extend(std::iter::repeat_n(spec.fill, after));
+
+    Ok(())
+}
+
+fn sign(negative: bool, plus: bool) -> &'static str {
+    match (negative, plus) {
+        (true, _) => "-",
+        (false, true) => "+",
+        (false, false) => "",
+    }
+}
+
+// The bits of `value` in a `bits` wide unsigned integer, e.g. `-1` in 8 bits
+// is `0xff`
+fn twos_complement(value: i64, bits: u32) -> u64 {
+    match bits {
+        1..=63 => value as u64 & (u64::MAX >> (64 - bits)),
+        _ => value as u64,
+    }
+}
+
+fn type_char(ty: Type) -> char {
+    match ty {
+        Type::Display => ' ',
+        Type::LowerHex => 'x',
+        Type::UpperHex => 'X',
+        Type::Octal => 'o',
+        Type::Binary => 'b',
+        Type::LowerExp => 'e',
+        Type::UpperExp => 'E',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, values: &[(&'static str, Value)]) -> String {
+        let values: HashMap<_, _> = values.iter().cloned().collect();
+
+        Template::parse(template).unwrap().render(&values).unwrap()
+    }
+
+    // Every spec must render exactly like `format!`
+    #[test]
+    fn test_matches_format() {
+        let int = |v: i64| vec![("v", Value::from(v))];
+        let float = |v: f64| vec![("v", Value::Float(v))];
+
+        for &v in &[0, 7, -7, 255, -1234567] {
+            assert_eq!(render("{v}", &int(v)), format!("{}", v));
+            assert_eq!(render("{v:>8}", &int(v)), format!("{:>8}", v));
+            assert_eq!(render("{v:*^9}", &int(v)), format!("{:*^9}", v));
+            assert_eq!(render("{v:+}", &int(v)), format!("{:+}", v));
+            assert_eq!(render("{v:08}", &int(v)), format!("{:08}", v));
+            assert_eq!(render("{v:+08}", &int(v)), format!("{:+08}", v));
+            assert_eq!(render("{v:e}", &int(v)), format!("{:e}", v));
+        }
+        for &v in &[0, 7, 255, 1234567] {
+            assert_eq!(render("{v:02X}", &int(v)), format!("{:02X}", v));
+            assert_eq!(render("{v:#x}", &int(v)), format!("{:#x}", v));
+            assert_eq!(render("{v:#010b}", &int(v)), format!("{:#010b}", v));
+            assert_eq!(render("{v:o}", &int(v)), format!("{:o}", v));
+        }
+        // Negative values keep the width of their type
+        for &v in &[-1, -7, -128] {
+            let narrow = |v| vec![("v", v)];
+
+            assert_eq!(render("{v:x}", &int(v)), format!("{:x}", v));
+            assert_eq!(
+                render("{v:x}", &narrow(Value::from(v as i8))),
+                format!("{:x}", v as i8)
+            );
+            assert_eq!(
+                render("{v:#X}", &narrow(Value::from(v as i16))),
+                format!("{:#X}", v as i16)
+            );
+            assert_eq!(
+                render("{v:o}", &narrow(Value::from(v as i32))),
+                format!("{:o}", v as i32)
+            );
+            assert_eq!(
+                render("{v:#010b}", &narrow(Value::from(v as i8))),
+                format!("{:#010b}", v as i8)
+            );
+            assert_eq!(
+                render("{v}", &narrow(Value::from(v as i8))),
+                format!("{}", v as i8)
+            );
+        }
+        for &v in &[0.0, 1.5, -2.25, 1.0 / 3.0, 6.02e23, -1e-7] {
+            assert_eq!(render("{v}", &float(v)), format!("{}", v));
+            assert_eq!(render("{v:.3}", &float(v)), format!("{:.3}", v));
+            assert_eq!(render("{v:>10.2}", &float(v)), format!("{:>10.2}", v));
+            assert_eq!(render("{v:+010.1}", &float(v)), format!("{:+010.1}", v));
+            assert_eq!(render("{v:.2e}", &float(v)), format!("{:.2e}", v));
+            assert_eq!(render("{v:E}", &float(v)), format!("{:E}", v));
+        }
+        for v in &["", "Rust", "naïve"] {
+            let s = vec![("v", Value::from(*v))];
+
+            assert_eq!(render("{v:8}|", &s), format!("{:8}|", v));
+            assert_eq!(render("{v:-^8}", &s), format!("{:-^8}", v));
+            assert_eq!(render("{v:.3}", &s), format!("{:.3}", v));
+        }
+    }
+
+    #[test]
+    fn test_escapes_and_literals() {
+        assert_eq!(
+            render("{{{v}}} and {v:>3}!", &[("v", Value::from(1))]),
+            "{1} and   1!"
+        );
+        assert_eq!(render("no arguments", &[]), "no arguments");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Template::parse("a {b"),
+            Err(ParseTemplateError::UnmatchedBrace(2))
+        );
+        assert_eq!(
+            Template::parse("a } b"),
+            Err(ParseTemplateError::UnmatchedBrace(2))
+        );
+        assert_eq!(
+            Template::parse("{:x}"),
+            Err(ParseTemplateError::MissingName(0))
+        );
+        assert_eq!(
+            Template::parse("{v:q}"),
+            Err(ParseTemplateError::InvalidSpec {
+                offset: 0,
+                spec: "q".to_owned()
+            })
+        );
+
+        let template = Template::parse("{v:x}").unwrap();
+        let values: HashMap<&str, Value> = HashMap::new();
+        assert_eq!(
+            template.render(&values),
+            Err(RenderError::MissingValue("v".to_owned()))
+        );
+
+        let values: HashMap<_, _> = vec![("v", Value::from("s"))].into_iter().collect();
+        assert_eq!(
+            template.render(&values),
+            Err(RenderError::TypeMismatch {
+                name: "v".to_owned(),
+                spec: 'x'
+            })
+        );
+    }
+}
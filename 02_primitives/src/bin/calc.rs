@@ -0,0 +1,1191 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// An interactive calculator for the literals and operators of 2.1:
+// `cargo run --manifest-path ./02_primitives/Cargo.toml --bin calc`
+// or, for single expressions:
+// `cargo run --manifest-path ./02_primitives/Cargo.toml --bin calc -- '1u8 << 7'`
+//
+// NOTE: literals are written as in Rust: `0x80u32`, `0b0011`, `1_000_000u32`,
+//       `1e4`, `2.5f32`, `true`
+//       Unsuffixed literals take their type from the other operand, or
+//       default to `i32` and `f64`, as the compiler would infer them
+//       Operators have Rust's precedence, from the tightest:
+//         unary `-` `!`, `as`, `* / %`, `+ -`, `<< >>`, `&`, `^`, `|`,
+//         comparisons, `&&`, `||`
+//       Integers behave as in debug builds: overflows are errors, so are
+//       out of range literals, also when negated in parentheses: `-(128i8)`
+//       128-bit integers are not supported, values are calculated in `i128`,
+//       which doesn't hold every `u128`, so `i128` and `u128` are rejected
+
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IntType {
+    I8,
+    I16,
+    I32,
+    I64,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+}
+
+impl IntType {
+    const ALL: [IntType; 10] = [
+        IntType::I8,
+        IntType::I16,
+        IntType::I32,
+        IntType::I64,
+        IntType::Isize,
+        IntType::U8,
+        IntType::U16,
+        IntType::U32,
+        IntType::U64,
+        IntType::Usize,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            IntType::I8 => "i8",
+            IntType::I16 => "i16",
+            IntType::I32 => "i32",
+            IntType::I64 => "i64",
+            IntType::Isize => "isize",
+            IntType::U8 => "u8",
+            IntType::U16 => "u16",
+            IntType::U32 => "u32",
+            IntType::U64 => "u64",
+            IntType::Usize => "usize",
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            IntType::I8 | IntType::U8 => 8,
+            IntType::I16 | IntType::U16 => 16,
+            IntType::I32 | IntType::U32 => 32,
+            IntType::I64 | IntType::U64 => 64,
+            IntType::Isize | IntType::Usize => usize::BITS,
+        }
+    }
+
+    fn is_signed(self) -> bool {
+        matches!(
+            self,
+            IntType::I8 | IntType::I16 | IntType::I32 | IntType::I64 | IntType::Isize
+        )
+    }
+
+    fn min(self) -> i128 {
+        if self.is_signed() {
+            -(1 << (self.bits() - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max(self) -> i128 {
+        if self.is_signed() {
+            (1 << (self.bits() - 1)) - 1
+        } else {
+            (1 << self.bits()) - 1
+        }
+    }
+
+    // Keep the low `bits`, as `as` does
+    fn wrap(self, value: i128) -> i128 {
+        let bits = self.bits();
+        let low = (value as u128) & ((1 << bits) - 1);
+
+        if self.is_signed() && low >> (bits - 1) == 1 {
+            low as i128 - (1 << bits)
+        } else {
+            low as i128
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FloatType {
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Int(IntType),
+    Float(FloatType),
+    Bool,
+}
+
+impl Type {
+    fn parse(s: &str) -> Option<Type> {
+        match s {
+            "f32" => Some(Type::Float(FloatType::F32)),
+            "f64" => Some(Type::Float(FloatType::F64)),
+            "bool" => Some(Type::Bool),
+            _ => IntType::ALL
+                .iter()
+                .find(|ty| ty.name() == s)
+                .map(|&ty| Type::Int(ty)),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Int(ty) => write!(f, "{}", ty.name()),
+            Type::Float(FloatType::F32) => write!(f, "f32"),
+            Type::Float(FloatType::F64) => write!(f, "f64"),
+            Type::Bool => write!(f, "bool"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(u128, Option<IntType>),
+    Float(f64, Option<FloatType>),
+    Bool(bool),
+    Type(Type),
+    As,
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    // `column` starts at 1
+    Syntax { column: usize, message: String },
+    Eval(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax { column, message } => write!(f, "column {}: {}", column, message),
+            Error::Eval(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+fn syntax<T>(column: usize, message: impl Into<String>) -> Result<T, Error> {
+    Err(Error::Syntax {
+        column,
+        message: message.into(),
+    })
+}
+
+fn eval_error<T>(message: impl Into<String>) -> Result<T, Error> {
+    Err(Error::Eval(message.into()))
+}
+
+const NO_128_BIT: &str = "128-bit integers are not supported";
+
+fn is_128_bit(name: &str) -> bool {
+    name == "i128" || name == "u128"
+}
+
+const OPERATORS: [&str; 18] = [
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "&", "|", "^", "<",
+    ">",
+];
+
+// Tokens with their columns
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let bytes = s.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+
+    while i < bytes.len() {
+        let column = i + 1;
+        let b = bytes[i];
+
+        if b.is_ascii_whitespace() {
+            i += 1;
+        } else if b.is_ascii_digit() {
+            let (token, len) =
+                number(&s[i..]).map_err(|message| Error::Syntax { column, message })?;
+            tokens.push((column, token));
+            i += len;
+        } else if is_word(b) {
+            let start = i;
+            while i < bytes.len() && is_word(bytes[i]) {
+                i += 1;
+            }
+
+            let token = match &s[start..i] {
+                "true" => Token::Bool(true),
+                "false" => Token::Bool(false),
+                "as" => Token::As,
+                word if is_128_bit(word) => return syntax(column, NO_128_BIT),
+                word => match Type::parse(word) {
+                    Some(ty) => Token::Type(ty),
+                    None => return syntax(column, format!("unknown identifier `{}`", word)),
+                },
+            };
+            tokens.push((column, token));
+        } else if b == b'(' || b == b')' {
+            tokens.push((column, if b == b'(' { Token::Open } else { Token::Close }));
+            i += 1;
+        } else if let Some(op) = OPERATORS.iter().find(|op| s[i..].starts_with(*op)) {
+            tokens.push((column, Token::Op(op)));
+            i += op.len();
+        } else if b == b'!' {
+            tokens.push((column, Token::Op("!")));
+            i += 1;
+        } else {
+            let c = s[i..].chars().next().unwrap_or('?');
+            return syntax(column, format!("unexpected `{}`", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// A literal at the start of `s`, and its length
+fn number(s: &str) -> Result<(Token, usize), String> {
+    let bytes = s.as_bytes();
+    let word_len = |from: usize| {
+        from + bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+            .count()
+    };
+
+    let radix = match bytes.get(..2) {
+        Some(b"0x") => 16,
+        Some(b"0o") => 8,
+        Some(b"0b") => 2,
+        _ => 10,
+    };
+
+    if radix != 10 {
+        // Hex digits include `f`, so only integer suffixes exist
+        let end = word_len(2);
+        let word = &s[2..end];
+        let suffix_start = word.find(['i', 'u']).unwrap_or(word.len());
+        let (digits, suffix) = word.split_at(suffix_start);
+
+        let suffix = int_suffix(suffix)?;
+        let value = int_digits(digits, radix)?;
+
+        return Ok((Token::Int(value, suffix), end));
+    }
+
+    let mut end = bytes
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || **b == b'_')
+        .count();
+    let mut is_float = false;
+
+    // `1.5`, and `1.` but not `1..` or `1.e3`
+    if bytes.get(end) == Some(&b'.')
+        && !bytes
+            .get(end + 1)
+            .is_some_and(|b| *b == b'.' || b.is_ascii_alphabetic() || *b == b'_')
+    {
+        is_float = true;
+        end += 1;
+        end += bytes[end..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b'_')
+            .count();
+    }
+
+    // The exponent
+    if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'+') | Some(b'-')) {
+            exponent += 1;
+        }
+        let digits = bytes[exponent..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b'_')
+            .count();
+
+        if bytes[exponent..exponent + digits]
+            .iter()
+            .any(u8::is_ascii_digit)
+        {
+            is_float = true;
+            end = exponent + digits;
+        }
+    }
+
+    let literal = s[..end].replace('_', "");
+    let suffix_end = word_len(end);
+    let suffix = &s[end..suffix_end];
+
+    let token = match suffix {
+        "f32" => Token::Float(float_digits(&literal)?, Some(FloatType::F32)),
+        "f64" => Token::Float(float_digits(&literal)?, Some(FloatType::F64)),
+        _ if is_float && suffix.is_empty() => Token::Float(float_digits(&literal)?, None),
+        _ if is_float => return Err(format!("invalid suffix `{}` for float literal", suffix)),
+        _ => Token::Int(int_digits(&literal, 10)?, int_suffix(suffix)?),
+    };
+
+    Ok((token, suffix_end))
+}
+
+fn int_suffix(suffix: &str) -> Result<Option<IntType>, String> {
+    if suffix.is_empty() {
+        return Ok(None);
+    }
+    if is_128_bit(suffix) {
+        return Err(NO_128_BIT.to_owned());
+    }
+
+    match Type::parse(suffix) {
+        Some(Type::Int(ty)) => Ok(Some(ty)),
+        _ => Err(format!("invalid suffix `{}` for number literal", suffix)),
+    }
+}
+
+fn int_digits(digits: &str, radix: u32) -> Result<u128, String> {
+    let digits = digits.replace('_', "");
+
+    if digits.is_empty() {
+        return Err("no valid digits found for number".to_owned());
+    }
+    if let Some(c) = digits.chars().find(|c| !c.is_digit(radix)) {
+        return Err(format!(
+            "invalid digit `{}` for a base {} literal",
+            c, radix
+        ));
+    }
+
+    u128::from_str_radix(&digits, radix).map_err(|_| "integer literal is too large".to_owned())
+}
+
+fn float_digits(literal: &str) -> Result<f64, String> {
+    literal
+        .parse()
+        .map_err(|_| format!("invalid float literal `{}`", literal))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinOp {
+    // From the loosest binding
+    fn from_symbol(symbol: &str) -> Option<(BinOp, u8)> {
+        let op = match symbol {
+            "||" => (BinOp::Or, 1),
+            "&&" => (BinOp::And, 2),
+            "==" => (BinOp::Eq, 3),
+            "!=" => (BinOp::Ne, 3),
+            "<" => (BinOp::Lt, 3),
+            "<=" => (BinOp::Le, 3),
+            ">" => (BinOp::Gt, 3),
+            ">=" => (BinOp::Ge, 3),
+            "|" => (BinOp::BitOr, 4),
+            "^" => (BinOp::BitXor, 5),
+            "&" => (BinOp::BitAnd, 6),
+            "<<" => (BinOp::Shl, 7),
+            ">>" => (BinOp::Shr, 7),
+            "+" => (BinOp::Add, 8),
+            "-" => (BinOp::Sub, 8),
+            "*" => (BinOp::Mul, 9),
+            "/" => (BinOp::Div, 9),
+            "%" => (BinOp::Rem, 9),
+            _ => return None,
+        };
+
+        Some(op)
+    }
+
+    fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Int(u128, Option<IntType>),
+    Float(f64, Option<FloatType>),
+    Bool(bool),
+    Unary(UnOp, Box<Expr>),
+    // Kept, so that `-(128i8)` is not the literal `-128i8`
+    Paren(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Cast(Box<Expr>, Type),
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    // For errors at the end of the input
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end_column, |&(column, _)| column)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_binary(&self) -> Option<(BinOp, u8)> {
+        match self.peek() {
+            Some(Token::Op(symbol)) => BinOp::from_symbol(symbol),
+            _ => None,
+        }
+    }
+
+    // Precedence climbing, all operators are left associative except the
+    // comparisons, which can't be chained
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, Error> {
+        let mut lhs = self.cast()?;
+
+        while let Some((op, precedence)) = self.peek_binary() {
+            if precedence < min_precedence {
+                break;
+            }
+            self.next();
+
+            let rhs = self.expression(precedence + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+
+            if op.is_comparison() && self.peek_binary().is_some_and(|(op, _)| op.is_comparison()) {
+                return syntax(self.column(), "comparison operators cannot be chained");
+            }
+        }
+
+        Ok(lhs)
+    }
+
+    fn cast(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.unary()?;
+
+        while self.peek() == Some(&Token::As) {
+            self.next();
+            let column = self.column();
+            match self.next() {
+                Some(Token::Type(ty)) => expr = Expr::Cast(Box::new(expr), ty),
+                _ => return syntax(column, "expected a type after `as`"),
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        let op = match self.peek() {
+            Some(Token::Op("-")) => UnOp::Neg,
+            Some(Token::Op("!")) => UnOp::Not,
+            _ => return self.primary(),
+        };
+        self.next();
+
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let column = self.column();
+
+        match self.next() {
+            Some(Token::Int(value, ty)) => Ok(Expr::Int(value, ty)),
+            Some(Token::Float(value, ty)) => Ok(Expr::Float(value, ty)),
+            Some(Token::Bool(value)) => Ok(Expr::Bool(value)),
+            Some(Token::Open) => {
+                let expr = self.expression(0)?;
+                let column = self.column();
+
+                match self.next() {
+                    Some(Token::Close) => Ok(Expr::Paren(Box::new(expr))),
+                    _ => syntax(column, "expected `)`"),
+                }
+            }
+            Some(_) | None => syntax(column, "expected an operand"),
+        }
+    }
+}
+
+fn parse(s: &str) -> Result<Expr, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        position: 0,
+        end_column: s.len() + 1,
+    };
+
+    let expr = parser.expression(0)?;
+
+    if parser.peek().is_some() {
+        return syntax(parser.column(), "expected an operator");
+    }
+
+    Ok(expr)
+}
+
+// The type of an expression as far as it doesn't depend on its context
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inferred {
+    Known(Type),
+    // An unsuffixed literal
+    AnyInt,
+    AnyFloat,
+}
+
+impl Inferred {
+    // `expected` comes from the context, literals default to `i32` and `f64`
+    fn resolve(self, expected: Option<Type>) -> Type {
+        match (self, expected) {
+            (Inferred::Known(ty), _) => ty,
+            (Inferred::AnyInt, Some(ty @ Type::Int(_))) => ty,
+            (Inferred::AnyInt, _) => Type::Int(IntType::I32),
+            (Inferred::AnyFloat, Some(ty @ Type::Float(_))) => ty,
+            (Inferred::AnyFloat, _) => Type::Float(FloatType::F64),
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Inferred::Known(ty) => format!("`{}`", ty),
+            Inferred::AnyInt => "`{integer}`".to_owned(),
+            Inferred::AnyFloat => "`{float}`".to_owned(),
+        }
+    }
+}
+
+fn unify(a: Inferred, b: Inferred) -> Result<Inferred, Error> {
+    match (a, b) {
+        _ if a == b => Ok(a),
+        (Inferred::Known(ty @ Type::Int(_)), Inferred::AnyInt)
+        | (Inferred::AnyInt, Inferred::Known(ty @ Type::Int(_)))
+        | (Inferred::Known(ty @ Type::Float(_)), Inferred::AnyFloat)
+        | (Inferred::AnyFloat, Inferred::Known(ty @ Type::Float(_))) => Ok(Inferred::Known(ty)),
+        _ => eval_error(format!(
+            "mismatched types {} and {}",
+            a.describe(),
+            b.describe()
+        )),
+    }
+}
+
+fn infer(expr: &Expr) -> Result<Inferred, Error> {
+    Ok(match expr {
+        Expr::Int(_, Some(ty)) => Inferred::Known(Type::Int(*ty)),
+        Expr::Int(_, None) => Inferred::AnyInt,
+        Expr::Float(_, Some(ty)) => Inferred::Known(Type::Float(*ty)),
+        Expr::Float(_, None) => Inferred::AnyFloat,
+        Expr::Bool(_) => Inferred::Known(Type::Bool),
+        Expr::Unary(_, expr) | Expr::Paren(expr) => infer(expr)?,
+        Expr::Cast(_, ty) => Inferred::Known(*ty),
+        Expr::Binary(BinOp::Shl, lhs, _) | Expr::Binary(BinOp::Shr, lhs, _) => infer(lhs)?,
+        Expr::Binary(op, _, _) if op.is_comparison() => Inferred::Known(Type::Bool),
+        Expr::Binary(BinOp::And, _, _) | Expr::Binary(BinOp::Or, _, _) => {
+            Inferred::Known(Type::Bool)
+        }
+        Expr::Binary(_, lhs, rhs) => unify(infer(lhs)?, infer(rhs)?)?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Int(i128, IntType),
+    Float(f64, FloatType),
+    Bool(bool),
+}
+
+impl Value {
+    fn ty(self) -> Type {
+        match self {
+            Value::Int(_, ty) => Type::Int(ty),
+            Value::Float(_, ty) => Type::Float(ty),
+            Value::Bool(_) => Type::Bool,
+        }
+    }
+}
+
+// Every notation of the value
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Int(value, ty) => {
+                // Two's complement, as `{:x}` shows negative integers
+                let bits = (value as u128) & ((1 << ty.bits()) - 1);
+
+                write!(
+                    f,
+                    "{} ({}) = {:#x} = {:#o} = {:#b}",
+                    value,
+                    ty.name(),
+                    bits,
+                    bits,
+                    bits
+                )
+            }
+            Value::Float(value, FloatType::F32) => {
+                let value = value as f32;
+                write!(
+                    f,
+                    "{} (f32) = {:e}, bits {:#010x}",
+                    value,
+                    value,
+                    value.to_bits()
+                )
+            }
+            Value::Float(value, FloatType::F64) => {
+                write!(
+                    f,
+                    "{} (f64) = {:e}, bits {:#018x}",
+                    value,
+                    value,
+                    value.to_bits()
+                )
+            }
+            Value::Bool(value) => write!(f, "{} (bool)", value),
+        }
+    }
+}
+
+fn evaluate(s: &str) -> Result<Value, Error> {
+    eval(&parse(s)?, None)
+}
+
+fn eval(expr: &Expr, expected: Option<Type>) -> Result<Value, Error> {
+    let ty = infer(expr)?.resolve(expected);
+
+    match expr {
+        Expr::Int(value, _) => int_literal(*value, false, ty),
+        Expr::Float(value, _) => match ty {
+            Type::Float(ty) => Ok(float(*value, ty)),
+            _ => eval_error(format!("expected `{}`, found floating-point number", ty)),
+        },
+        Expr::Bool(value) => Ok(Value::Bool(*value)),
+        // `-128i8` is a literal, `128i8` alone is out of range
+        Expr::Unary(UnOp::Neg, literal) if matches!(**literal, Expr::Int(..)) => match **literal {
+            Expr::Int(value, _) => int_literal(value, true, ty),
+            _ => unreachable!(),
+        },
+        Expr::Unary(op, operand) => unary(*op, eval(operand, Some(ty))?),
+        Expr::Paren(expr) => eval(expr, expected),
+        Expr::Cast(operand, ty) => cast(eval(operand, None)?, *ty),
+        Expr::Binary(BinOp::And, lhs, rhs) | Expr::Binary(BinOp::Or, lhs, rhs) => {
+            let is_and = matches!(expr, Expr::Binary(BinOp::And, ..));
+            let lhs = boolean(eval(lhs, Some(Type::Bool))?)?;
+
+            // Short-circuit: the right side isn't evaluated at all
+            if lhs != is_and {
+                return Ok(Value::Bool(lhs));
+            }
+
+            Ok(Value::Bool(boolean(eval(rhs, Some(Type::Bool))?)?))
+        }
+        Expr::Binary(op @ BinOp::Shl, lhs, rhs) | Expr::Binary(op @ BinOp::Shr, lhs, rhs) => {
+            let lhs = eval(lhs, Some(ty))?;
+            let rhs = eval(rhs, None)?;
+
+            shift(*op, lhs, rhs)
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let operands = unify(infer(lhs)?, infer(rhs)?)?;
+            let operand_ty = if op.is_comparison() {
+                operands.resolve(None)
+            } else {
+                operands.resolve(expected)
+            };
+
+            binary(
+                *op,
+                eval(lhs, Some(operand_ty))?,
+                eval(rhs, Some(operand_ty))?,
+            )
+        }
+    }
+}
+
+fn int_literal(value: u128, negative: bool, ty: Type) -> Result<Value, Error> {
+    let ty = match ty {
+        Type::Int(ty) => ty,
+        Type::Float(ty) => {
+            let value = value as f64;
+            return Ok(float(if negative { -value } else { value }, ty));
+        }
+        Type::Bool => return eval_error("expected `bool`, found integer"),
+    };
+
+    let value = i128::try_from(value)
+        .ok()
+        .map(|v| if negative { -v } else { v });
+
+    match value {
+        Some(value) if (ty.min()..=ty.max()).contains(&value) => Ok(Value::Int(value, ty)),
+        _ if negative && !ty.is_signed() => eval_error(format!(
+            "cannot apply unary operator `-` to type `{}`",
+            ty.name()
+        )),
+        _ => eval_error(format!("literal out of range for `{}`", ty.name())),
+    }
+}
+
+// Rounded to `f32` if needed
+fn float(value: f64, ty: FloatType) -> Value {
+    match ty {
+        FloatType::F32 => Value::Float(value as f32 as f64, ty),
+        FloatType::F64 => Value::Float(value, ty),
+    }
+}
+
+fn boolean(value: Value) -> Result<bool, Error> {
+    match value {
+        Value::Bool(value) => Ok(value),
+        value => eval_error(format!("expected `bool`, found `{}`", value.ty())),
+    }
+}
+
+fn unary(op: UnOp, value: Value) -> Result<Value, Error> {
+    match (op, value) {
+        (UnOp::Neg, Value::Int(v, ty)) if ty.is_signed() => {
+            checked(-v, ty, "attempt to negate with overflow")
+        }
+        (UnOp::Neg, Value::Float(v, ty)) => Ok(Value::Float(-v, ty)),
+        (UnOp::Not, Value::Int(v, ty)) => Ok(Value::Int(ty.wrap(!v), ty)),
+        (UnOp::Not, Value::Bool(v)) => Ok(Value::Bool(!v)),
+        (UnOp::Neg, value) => eval_error(format!(
+            "cannot apply unary operator `-` to type `{}`",
+            value.ty()
+        )),
+        (UnOp::Not, value) => eval_error(format!(
+            "cannot apply unary operator `!` to type `{}`",
+            value.ty()
+        )),
+    }
+}
+
+fn checked(value: i128, ty: IntType, message: &str) -> Result<Value, Error> {
+    if (ty.min()..=ty.max()).contains(&value) {
+        Ok(Value::Int(value, ty))
+    } else {
+        eval_error(message)
+    }
+}
+
+fn shift(op: BinOp, lhs: Value, rhs: Value) -> Result<Value, Error> {
+    let (value, ty, amount) = match (lhs, rhs) {
+        (Value::Int(value, ty), Value::Int(amount, _)) => (value, ty, amount),
+        _ => {
+            return eval_error(format!(
+                "no implementation for `{} {} {}`",
+                lhs.ty(),
+                if op == BinOp::Shl { "<<" } else { ">>" },
+                rhs.ty()
+            ))
+        }
+    };
+
+    // Only the shift amount can overflow, not the shifted value
+    if !(0..ty.bits() as i128).contains(&amount) {
+        return eval_error(if op == BinOp::Shl {
+            "attempt to shift left with overflow"
+        } else {
+            "attempt to shift right with overflow"
+        });
+    }
+
+    let value = if op == BinOp::Shl {
+        ty.wrap(value.wrapping_shl(amount as u32))
+    } else {
+        value >> amount
+    };
+
+    Ok(Value::Int(value, ty))
+}
+
+macro_rules! float_binary {
+    ($op:expr, $a:expr, $b:expr) => {
+        match $op {
+            BinOp::Add => Some(Value::Float(($a + $b) as f64, FloatType::F64)),
+            BinOp::Sub => Some(Value::Float(($a - $b) as f64, FloatType::F64)),
+            BinOp::Mul => Some(Value::Float(($a * $b) as f64, FloatType::F64)),
+            BinOp::Div => Some(Value::Float(($a / $b) as f64, FloatType::F64)),
+            BinOp::Rem => Some(Value::Float(($a % $b) as f64, FloatType::F64)),
+            BinOp::Eq => Some(Value::Bool($a == $b)),
+            BinOp::Ne => Some(Value::Bool($a != $b)),
+            BinOp::Lt => Some(Value::Bool($a < $b)),
+            BinOp::Le => Some(Value::Bool($a <= $b)),
+            BinOp::Gt => Some(Value::Bool($a > $b)),
+            BinOp::Ge => Some(Value::Bool($a >= $b)),
+            _ => None,
+        }
+    };
+}
+
+fn binary(op: BinOp, lhs: Value, rhs: Value) -> Result<Value, Error> {
+    let result = match (lhs, rhs) {
+        (Value::Int(a, ty), Value::Int(b, _)) => return int_binary(op, a, b, ty),
+        // Calculated in the precision of the type
+        (Value::Float(a, FloatType::F32), Value::Float(b, _)) => {
+            float_binary!(op, a as f32, b as f32).map(|value| match value {
+                Value::Float(value, _) => Value::Float(value, FloatType::F32),
+                value => value,
+            })
+        }
+        (Value::Float(a, FloatType::F64), Value::Float(b, _)) => float_binary!(op, a, b),
+        (Value::Bool(a), Value::Bool(b)) => match op {
+            BinOp::BitAnd => Some(Value::Bool(a & b)),
+            BinOp::BitOr => Some(Value::Bool(a | b)),
+            BinOp::BitXor => Some(Value::Bool(a ^ b)),
+            BinOp::Eq => Some(Value::Bool(a == b)),
+            BinOp::Ne => Some(Value::Bool(a != b)),
+            BinOp::Lt => Some(Value::Bool(!a & b)),
+            BinOp::Le => Some(Value::Bool(a <= b)),
+            BinOp::Gt => Some(Value::Bool(a & !b)),
+            BinOp::Ge => Some(Value::Bool(a >= b)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match result {
+        Some(value) => Ok(value),
+        None => eval_error(format!(
+            "no implementation for `{} {:?} {}`",
+            lhs.ty(),
+            op,
+            rhs.ty()
+        )),
+    }
+}
+
+fn int_binary(op: BinOp, a: i128, b: i128, ty: IntType) -> Result<Value, Error> {
+    // `i128` holds every result of the 64-bit types, except some products
+    let overflow = |value: Option<i128>, message| match value {
+        Some(value) => checked(value, ty, message),
+        None => eval_error(message),
+    };
+
+    match op {
+        BinOp::Add => overflow(a.checked_add(b), "attempt to add with overflow"),
+        BinOp::Sub => overflow(a.checked_sub(b), "attempt to subtract with overflow"),
+        BinOp::Mul => overflow(a.checked_mul(b), "attempt to multiply with overflow"),
+        BinOp::Div if b == 0 => eval_error("attempt to divide by zero"),
+        BinOp::Div => checked(a / b, ty, "attempt to divide with overflow"),
+        BinOp::Rem if b == 0 => {
+            eval_error("attempt to calculate the remainder with a divisor of zero")
+        }
+        BinOp::Rem if a == ty.min() && b == -1 => {
+            eval_error("attempt to calculate the remainder with overflow")
+        }
+        BinOp::Rem => Ok(Value::Int(a % b, ty)),
+        BinOp::BitAnd => Ok(Value::Int(a & b, ty)),
+        BinOp::BitOr => Ok(Value::Int(a | b, ty)),
+        BinOp::BitXor => Ok(Value::Int(a ^ b, ty)),
+        BinOp::Eq => Ok(Value::Bool(a == b)),
+        BinOp::Ne => Ok(Value::Bool(a != b)),
+        BinOp::Lt => Ok(Value::Bool(a < b)),
+        BinOp::Le => Ok(Value::Bool(a <= b)),
+        BinOp::Gt => Ok(Value::Bool(a > b)),
+        BinOp::Ge => Ok(Value::Bool(a >= b)),
+        BinOp::Shl | BinOp::Shr | BinOp::And | BinOp::Or => {
+            unreachable!("handled by `eval`")
+        }
+    }
+}
+
+// `as`: integers wrap, floats saturate towards integers, NaN becomes `0`
+fn cast(value: Value, ty: Type) -> Result<Value, Error> {
+    let result = match (value, ty) {
+        (Value::Int(v, _), Type::Int(ty)) => Value::Int(ty.wrap(v), ty),
+        (Value::Int(v, _), Type::Float(ty)) => float(v as f64, ty),
+        (Value::Float(v, _), Type::Int(ty)) => {
+            let v = if v.is_nan() {
+                0
+            } else if v <= ty.min() as f64 {
+                ty.min()
+            } else if v >= ty.max() as f64 {
+                ty.max()
+            } else {
+                v as i128
+            };
+            Value::Int(v, ty)
+        }
+        (Value::Float(v, _), Type::Float(ty)) => float(v, ty),
+        (Value::Bool(v), Type::Int(ty)) => Value::Int(v as i128, ty),
+        (Value::Bool(v), Type::Bool) => Value::Bool(v),
+        (value, ty) => {
+            return eval_error(format!("casting `{}` as `{}` is invalid", value.ty(), ty))
+        }
+    };
+
+    Ok(result)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        for expr in &args {
+            match evaluate(expr) {
+                Ok(value) => println!("{}", value),
+                Err(e) => println!("error: {}", e),
+            }
+        }
+        return;
+    }
+
+    println!("Rust literals and operators, e.g. `0b0011u32 & 0b0101`, `exit` quits");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("> ");
+        // Nothing to do about a closed stdout but to stop
+        if io::stdout().flush().is_err() {
+            break;
+        }
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        let line = line.trim();
+
+        match line {
+            "" => continue,
+            "exit" | "quit" => break,
+            _ => match evaluate(line) {
+                Ok(value) => println!("{}", value),
+                // `> ` is 2 columns wide
+                Err(e @ Error::Syntax { column, .. }) => {
+                    println!("{:>width$}", "^", width = column + 2);
+                    println!("error: {}", e)
+                }
+                Err(e) => println!("error: {}", e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(s: &str) -> (i128, &'static str) {
+        match evaluate(s) {
+            Ok(Value::Int(value, ty)) => (value, ty.name()),
+            other => panic!("{}: {:?}", s, other),
+        }
+    }
+
+    fn error(s: &str) -> String {
+        match evaluate(s) {
+            Err(e) => e.to_string(),
+            Ok(value) => panic!("{}: {}", s, value),
+        }
+    }
+
+    // The expressions of 2.1
+    #[test]
+    fn test_section_literals() {
+        assert_eq!(int("1u32 + 2"), (3, "u32"));
+        assert_eq!(int("1i32 - 2"), (-1, "i32"));
+        assert_eq!(error("1u32 - 2"), "attempt to subtract with overflow");
+        assert_eq!(evaluate("true && false"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("true || false"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("!true"), Ok(Value::Bool(false)));
+        assert_eq!(int("0b0011u32 & 0b0101"), (0b0001, "u32"));
+        assert_eq!(int("0b0011u32 | 0b0101"), (0b0111, "u32"));
+        assert_eq!(int("0b0011u32 ^ 0b0101"), (0b0110, "u32"));
+        assert_eq!(int("1u32 << 5"), (32, "u32"));
+        assert_eq!(int("0x80u32 >> 2"), (0x20, "u32"));
+        assert_eq!(int("1_000_000u32"), (1_000_000, "u32"));
+        assert_eq!(evaluate("1e4"), Ok(Value::Float(1e4, FloatType::F64)));
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(int("1 + 2 * 3"), (7, "i32"));
+        assert_eq!(int("(1 + 2) * 3"), (9, "i32"));
+        assert_eq!(int("1 << 2 + 1"), (8, "i32"));
+        assert_eq!(int("6 & 3 ^ 1 | 8"), (11, "i32"));
+        assert_eq!(int("-2 * -3"), (6, "i32"));
+        assert_eq!(int("10 - 4 - 3"), (3, "i32"));
+        assert_eq!(int("-1 as u8 as i32"), (255, "i32"));
+        assert_eq!(evaluate("2 & 3 == 2"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("1 < 2 && 3 > 4 || true"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_inference() {
+        assert_eq!(int("1 + 1u64"), (2, "u64"));
+        assert_eq!(int("3_000_000_000 + 1u64"), (3_000_000_001, "u64"));
+        assert_eq!(error("3_000_000_000"), "literal out of range for `i32`");
+        assert_eq!(int("1u8 << 7"), (128, "u8"));
+        assert_eq!(int("0xffi64"), (255, "i64"));
+        assert_eq!(int("0x1f32"), (0x1f32, "i32"));
+        assert_eq!(evaluate("1f32"), Ok(Value::Float(1.0, FloatType::F32)));
+        assert_eq!(
+            evaluate("2.5 * 2f32"),
+            Ok(Value::Float(5.0, FloatType::F32))
+        );
+        assert_eq!(
+            error("1 + 1.0"),
+            "mismatched types `{integer}` and `{float}`"
+        );
+        assert_eq!(error("1u8 + 1u16"), "mismatched types `u8` and `u16`");
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(error("255u8 + 1"), "attempt to add with overflow");
+        assert_eq!(error("200u8 * 2"), "attempt to multiply with overflow");
+        assert_eq!(error("max + 1"), "column 1: unknown identifier `max`");
+        assert_eq!(
+            error("18446744073709551615u64 * 18446744073709551615u64"),
+            "attempt to multiply with overflow"
+        );
+        assert_eq!(int("-128i8"), (-128, "i8"));
+        assert_eq!(error("128i8"), "literal out of range for `i8`");
+        assert_eq!(error("-(-128i8)"), "attempt to negate with overflow");
+        assert_eq!(error("-(128i8)"), "literal out of range for `i8`");
+        assert_eq!(int("-(127i8)"), (-127, "i8"));
+        assert_eq!(int("(-128i8)"), (-128, "i8"));
+        assert_eq!(error("-2147483648 / -1"), "attempt to divide with overflow");
+        assert_eq!(
+            error("-2147483648 % -1"),
+            "attempt to calculate the remainder with overflow"
+        );
+        assert_eq!(error("1 / 0"), "attempt to divide by zero");
+        assert_eq!(error("1u8 << 8"), "attempt to shift left with overflow");
+        assert_eq!(error("1 >> -1"), "attempt to shift right with overflow");
+        assert_eq!(int("255u8 << 1"), (254, "u8"));
+        assert_eq!(int("-7 / 2"), (-3, "i32"));
+        assert_eq!(int("-7 % 2"), (-1, "i32"));
+        assert_eq!(int("-16i8 >> 2"), (-4, "i8"));
+        assert_eq!(int("!0u8"), (255, "u8"));
+        assert_eq!(
+            error("-1u32"),
+            "cannot apply unary operator `-` to type `u32`"
+        );
+    }
+
+    #[test]
+    fn test_casts() {
+        assert_eq!(int("-1i32 as u8"), (255, "u8"));
+        assert_eq!(int("300 as u8"), (44, "u8"));
+        assert_eq!(int("1e10 as i32"), (i32::MAX as i128, "i32"));
+        assert_eq!(int("-1.5 as u32"), (0, "u32"));
+        assert_eq!(int("(0.0 / 0.0) as u8"), (0, "u8"));
+        assert_eq!(int("true as u8"), (1, "u8"));
+        assert_eq!(error("1 as bool"), "casting `i32` as `bool` is invalid");
+    }
+
+    #[test]
+    fn test_floats() {
+        // `f32` arithmetic is rounded to `f32`
+        let sum = evaluate("0.1f32 + 0.2").unwrap();
+        assert_eq!(sum, Value::Float(0.3f32 as f64, FloatType::F32));
+        assert_eq!(
+            evaluate("0.1 + 0.2"),
+            Ok(Value::Float(0.1 + 0.2, FloatType::F64))
+        );
+        assert_eq!(
+            evaluate("1.0 / 0.0"),
+            Ok(Value::Float(f64::INFINITY, FloatType::F64))
+        );
+        assert_eq!(error("1.5 & 1.0"), "no implementation for `f64 BitAnd f64`");
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        assert_eq!(evaluate("false && 1 / 0 == 0"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("true || 1 / 0 == 0"), Ok(Value::Bool(true)));
+        assert_eq!(error("true && 1 / 0 == 0"), "attempt to divide by zero");
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert_eq!(error("(1 + 2"), "column 7: expected `)`");
+        assert_eq!(error("1 +"), "column 4: expected an operand");
+        assert_eq!(error("1 2"), "column 3: expected an operator");
+        assert_eq!(
+            error("1 < 2 < 3"),
+            "column 7: comparison operators cannot be chained"
+        );
+        assert_eq!(
+            error("0b102"),
+            "column 1: invalid digit `2` for a base 2 literal"
+        );
+        assert_eq!(
+            error("1u7"),
+            "column 1: invalid suffix `u7` for number literal"
+        );
+        assert_eq!(error("0x"), "column 1: no valid digits found for number");
+        assert_eq!(
+            error("1u128"),
+            "column 1: 128-bit integers are not supported"
+        );
+        assert_eq!(
+            error("0xffi128 + 1"),
+            "column 1: 128-bit integers are not supported"
+        );
+        assert_eq!(
+            error("1 as u128"),
+            "column 6: 128-bit integers are not supported"
+        );
+        assert_eq!(error("1 @ 2"), "column 3: unexpected `@`");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            evaluate("-1i8").unwrap().to_string(),
+            "-1 (i8) = 0xff = 0o377 = 0b11111111"
+        );
+        assert_eq!(
+            evaluate("1e4").unwrap().to_string(),
+            "10000 (f64) = 1e4, bits 0x40c3880000000000"
+        );
+    }
+}
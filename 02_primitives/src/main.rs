@@ -6,6 +6,10 @@
 //    2.2. Tuples
 //    2.3. Arrays and Slices
 
+mod matrix;
+mod stats;
+mod tuples;
+
 fn main() {
     // https://doc.rust-lang.org/rust-by-example/primitives.html
     println!();
@@ -79,8 +83,8 @@ fn main() {
         }
 
         // The following struct is for the activity.
-        #[derive(Debug)]
-        struct Matrix(f32, f32, f32, f32);
+        // NOTE: `Matrix` moved to `matrix.rs` and became `R`×`C`
+        use matrix::Matrix;
 
         // A tuple with a bunch of different types
         let long_tuple = (
@@ -107,6 +111,36 @@ fn main() {
 
         println!("the reversed pair is {:?}", reverse(pair));
 
+        // NOTE: `tuples.rs` reverses, extends, maps and prints any tuple of
+        //       up to 12 elements
+        {
+            use tuples::{Homogeneous, Tuple};
+
+            println!(
+                "the reversed long tuple is {}",
+                long_tuple.reverse().display()
+            );
+            println!("pushed in front: {}", pair.push_front("first").display());
+            println!("pushed at the back: {}", pair.push_back('z').display());
+
+            let squares = (1, 2, 3, 4).map_all(|x| x * x);
+            println!(
+                "squares: {}, as an array: {:?}",
+                squares.display(),
+                squares.to_array()
+            );
+            println!("{:.2}", (0.5, 1.25, 3.0).display());
+
+            fn len<T: Tuple>(_: &T) -> usize {
+                T::LEN
+            }
+            println!("the long tuple has {} elements", len(&long_tuple));
+
+            // A 13th element is fine, only printing it isn't
+            let too_long_tuple = long_tuple.push_back(13);
+            println!("too long tuple last value: {}", too_long_tuple.12);
+        }
+
         // To create one element tuples, the comma is required to tell them apart
         // from a literal surrounded by parentheses
         println!("one element tuple: {:?}", (5u32,));
@@ -118,7 +152,7 @@ fn main() {
         let (a, b, c, d) = tuple;
         println!("{:?}, {:?}, {:?}, {:?}", a, b, c, d);
 
-        let matrix = Matrix(1.1, 1.2, 2.1, 2.2);
+        let matrix = Matrix([[1.1f32, 1.2], [2.1, 2.2]]);
         println!("{:?}", matrix);
 
         println!();
@@ -142,20 +176,39 @@ fn main() {
         //            > ( 1.1 2.1 )
         //            > ( 1.2 2.2 )
 
-        use std::fmt;
-
-        impl fmt::Display for Matrix {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "( {} {} )\n( {} {} )", self.0, self.1, self.2, self.3)
-            }
-        }
-
-        fn transpose(Matrix(a, b, c, d): Matrix) -> Matrix {
-            Matrix(a, c, b, d)
+        fn transpose<const R: usize, const C: usize>(
+            matrix: Matrix<f32, R, C>,
+        ) -> Matrix<f32, C, R> {
+            matrix.transpose()
         }
 
         println!("Matrix:\n{}", matrix);
         println!("Transpose:\n{}", transpose(matrix));
+
+        // NOTE: the rest of the `Matrix` API
+        let a = Matrix([[2.0, 1.0, 0.5], [-1.0, 3.0, 4.0]]);
+        let b = Matrix([[1.0, 0.0], [0.0, 1.0], [10.0, -2.0]]);
+        let square = a * b;
+
+        println!("A:\n{}", a);
+        println!("B:\n{}", b);
+        println!("A * B:\n{}", square);
+        println!("det(A * B) = {}", square.determinant());
+        match square.inverse() {
+            Some(inverse) => println!("(A * B)^-1:\n{:.4}", inverse),
+            None => println!("A * B is singular"),
+        }
+        println!(
+            "(A * B) * (A * B)^-1:\n{:.1}",
+            square * square.inverse().unwrap()
+        );
+        println!("I3:\n{}", Matrix::<i32, 3, 3>::identity());
+        for (j, column) in a.columns().enumerate() {
+            println!("column {} of A sums to {}", j, column.sum::<f64>());
+        }
+        for row in a.rows() {
+            println!("row of A: {:?}", row);
+        }
     }
 
     // https://doc.rust-lang.org/rust-by-example/primitives/array.html
@@ -164,10 +217,14 @@ fn main() {
     {
         use std::mem;
 
+        use stats::SliceStats;
+
         // This function borrows a slice
         fn analyze_slice(slice: &[i32]) {
             println!("first element of the slice: {}", slice[0]);
             println!("the slice has {} elements", slice.len());
+            // NOTE: and the rest of what there is to know about it
+            println!("{}", SliceStats::new(slice));
         }
 
         // Fixed-size array (type signature is superfluous)
@@ -199,5 +256,27 @@ fn main() {
 
         // Out of bound indexing causes compile error
         // println!("{}", xs[5]);
+
+        // NOTE: `SliceStats` works with any numeric slice
+        let temperatures = [
+            21.5f32, 23.0, 19.8, 25.1, 22.4, 22.4, 18.9, 30.2, 24.7, 21.1, 20.3, 22.4,
+        ];
+        let stats = SliceStats::new(&temperatures);
+
+        println!("{:.2}", stats);
+        println!("p5: {:.2}", stats.percentile(5.0).unwrap_or(f64::NAN));
+        println!("3rd coldest: {:?}", stats.nth_smallest(2));
+        let histogram = stats.histogram(4);
+        println!("{}", histogram);
+        println!("bins: {:?}", histogram.counts());
+
+        // The sum is checked, the mean is calculated in `f64`
+        let bytes = [200u8, 100, 50];
+        println!(
+            "sum of {:?}: {:?}, mean: {:?}",
+            bytes,
+            SliceStats::new(&bytes).sum(),
+            SliceStats::new(&bytes).mean()
+        );
     }
 }
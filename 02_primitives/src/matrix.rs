@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 2.2 activity `Matrix(f32, f32, f32, f32)` grown into an `R`×`C`
+//       matrix of any numeric type, the elements are stored row by row:
+//         Matrix([[1.1, 1.2], [2.1, 2.2]])
+//       `Display` aligns the columns and keeps the activity's style:
+//         ( 1.1 1.2 )
+//         ( 2.1 2.2 )
+
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// The element types, `Default` is their zero
+pub trait Scalar:
+    Copy + Default + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ONE: Self;
+}
+
+// Scalars with an exact enough division, for determinants and inverses
+pub trait Field: Scalar + PartialOrd + Div<Output = Self> + Neg<Output = Self> {
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_scalar {
+    ($($t:ty: $one:expr),*) => {
+        $(
+            impl Scalar for $t {
+                const ONE: Self = $one;
+            }
+        )*
+    };
+}
+
+impl_scalar!(i8: 1, i16: 1, i32: 1, i64: 1, u8: 1, u16: 1, u32: 1, u64: 1, f32: 1.0, f64: 1.0);
+
+impl Field for f32 {
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+impl Field for f64 {
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix<T, const R: usize, const C: usize>(pub [[T; C]; R]);
+
+impl<T: Copy + Default, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub fn transpose(&self) -> Matrix<T, C, R> {
+        let mut rows = [[T::default(); R]; C];
+
+        for (i, row) in self.0.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                rows[j][i] = value;
+            }
+        }
+
+        Matrix(rows)
+    }
+}
+
+impl<T, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub fn rows(&self) -> impl Iterator<Item = &[T; C]> {
+        self.0.iter()
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        self.0.iter().map(move |row| &row[j])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..C).map(move |j| self.column(j))
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Default for Matrix<T, R, C> {
+    fn default() -> Self {
+        Matrix([[T::default(); C]; R])
+    }
+}
+
+impl<T: Scalar, const N: usize> Matrix<T, N, N> {
+    pub fn identity() -> Self {
+        let mut identity = Matrix::default();
+
+        for i in 0..N {
+            identity.0[i][i] = T::ONE;
+        }
+
+        identity
+    }
+}
+
+impl<T: Field, const N: usize> Matrix<T, N, N> {
+    // Gaussian elimination with partial pivoting
+    pub fn determinant(&self) -> T {
+        let mut rows = self.0;
+        let mut det = T::ONE;
+
+        for col in 0..N {
+            let pivot = match pivot_row(&rows, col) {
+                Some(pivot) => pivot,
+                None => return T::default(),
+            };
+
+            if pivot != col {
+                rows.swap(pivot, col);
+                det = -det;
+            }
+            det = det * rows[col][col];
+
+            let (upper, lower) = rows.split_at_mut(col + 1);
+            let pivot = &upper[col];
+            for row in lower {
+                let factor = row[col] / pivot[col];
+                for (value, &p) in row[col..].iter_mut().zip(&pivot[col..]) {
+                    *value = *value - factor * p;
+                }
+            }
+        }
+
+        det
+    }
+
+    // Gauss-Jordan elimination, `None` for singular matrices
+    pub fn inverse(&self) -> Option<Self> {
+        let mut rows = self.0;
+        let mut inverse = Matrix::<T, N, N>::identity().0;
+
+        for col in 0..N {
+            let pivot = pivot_row(&rows, col)?;
+            rows.swap(pivot, col);
+            inverse.swap(pivot, col);
+
+            let scale = rows[col][col];
+            for k in 0..N {
+                rows[col][k] = rows[col][k] / scale;
+                inverse[col][k] = inverse[col][k] / scale;
+            }
+
+            for row in 0..N {
+                if row == col {
+                    continue;
+                }
+
+                let factor = rows[row][col];
+                for k in 0..N {
+                    rows[row][k] = rows[row][k] - factor * rows[col][k];
+                    inverse[row][k] = inverse[row][k] - factor * inverse[col][k];
+                }
+            }
+        }
+
+        Some(Matrix(inverse))
+    }
+}
+
+// The row at or below `col` with the largest value in `col`, if not zero
+fn pivot_row<T: Field, const N: usize>(rows: &[[T; N]; N], col: usize) -> Option<usize> {
+    let pivot = (col..N).fold(col, |best, row| {
+        if rows[row][col].abs() > rows[best][col].abs() {
+            row
+        } else {
+            best
+        }
+    });
+
+    if rows[pivot][col] == T::default() {
+        None
+    } else {
+        Some(pivot)
+    }
+}
+
+impl<T: Scalar, const R: usize, const K: usize, const C: usize> Mul<Matrix<T, K, C>>
+    for Matrix<T, R, K>
+{
+    type Output = Matrix<T, R, C>;
+
+    fn mul(self, rhs: Matrix<T, K, C>) -> Matrix<T, R, C> {
+        let mut product = Matrix::default();
+
+        for i in 0..R {
+            for j in 0..C {
+                product.0[i][j] = self.0[i]
+                    .iter()
+                    .zip(rhs.column(j))
+                    .fold(T::default(), |sum, (&a, &b)| sum + a * b);
+            }
+        }
+
+        product
+    }
+}
+
+// Every column is as wide as its widest element, a precision, e.g. `{:.2}`,
+// applies to the elements
+impl<T: fmt::Display, const R: usize, const C: usize> fmt::Display for Matrix<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<Vec<String>> = self
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|value| match f.precision() {
+                        Some(precision) => format!("{:.*}", precision, value),
+                        None => value.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..C)
+            .map(|j| {
+                cells
+                    .iter()
+                    .map(|row| row[j].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for (i, row) in cells.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "(")?;
+            for (cell, width) in row.iter().zip(&widths) {
+                write!(f, " {:>width$}", cell, width = width)?;
+            }
+            write!(f, " )")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The output of the 2.2 activity
+    #[test]
+    fn test_transpose_activity() {
+        let matrix = Matrix([[1.1f32, 1.2], [2.1, 2.2]]);
+
+        assert_eq!(matrix.to_string(), "( 1.1 1.2 )\n( 2.1 2.2 )");
+        assert_eq!(matrix.transpose().to_string(), "( 1.1 2.1 )\n( 1.2 2.2 )");
+    }
+
+    #[test]
+    fn test_display_alignment() {
+        let matrix = Matrix([[1, -20, 3], [400, 5, 6]]);
+
+        assert_eq!(matrix.to_string(), "(   1 -20 3 )\n( 400   5 6 )");
+        assert_eq!(format!("{:.1}", Matrix([[1.0, 0.26]])), "( 1.0 0.3 )");
+    }
+
+    #[test]
+    fn test_multiplication() {
+        let a = Matrix([[1, 2, 3], [4, 5, 6]]);
+        let b = Matrix([[7, 8], [9, 10], [11, 12]]);
+
+        assert_eq!(a * b, Matrix([[58, 64], [139, 154]]));
+        assert_eq!(a * Matrix::identity(), a);
+        assert_eq!(Matrix::identity() * a, a);
+        assert_eq!((a * b).transpose(), b.transpose() * a.transpose());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let a = Matrix([[1, 2, 3], [4, 5, 6]]);
+
+        assert_eq!(a.rows().nth(1), Some(&[4, 5, 6]));
+        assert_eq!(
+            a.columns()
+                .map(|column| column.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+    }
+
+    #[test]
+    fn test_determinant_and_inverse() {
+        let a = Matrix([[0.0, 2.0, 1.0], [1.0, 1.0, 0.0], [2.0, 0.0, 3.0]]);
+
+        assert_eq!(a.determinant(), -8.0);
+        assert_eq!(Matrix([[1.0, 2.0], [2.0, 4.0]]).determinant(), 0.0);
+        assert_eq!(Matrix::<f64, 3, 3>::identity().determinant(), 1.0);
+
+        let inverse = a.inverse().unwrap();
+        for (row, expected) in (a * inverse)
+            .rows()
+            .zip(Matrix::<f64, 3, 3>::identity().rows())
+        {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!((value - expected).abs() < 1e-12);
+            }
+        }
+
+        assert_eq!(Matrix([[1.0, 2.0], [2.0, 4.0]]).inverse(), None);
+    }
+}
@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: what `analyze_slice` of 2.3 could tell about a slice, for any
+//       numeric element type
+//       The order statistics (min, max, median, percentiles) neither
+//       allocate nor reorder the slice: the k-th smallest element is found
+//       by narrowing a range of values, one pass over the slice per step
+//       Floats are ordered with `total_cmp`, so NaNs don't break anything
+
+use std::cmp::Ordering;
+use std::fmt;
+
+// Width of the longest histogram bar
+const MAX_BAR: usize = 40;
+
+pub trait Number: Copy + fmt::Display {
+    // `None` on overflow, or on a non finite float result
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn to_f64(self) -> f64;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! impl_number_int {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    self.cmp(other)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_number_float {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    Some(self + other).filter(|sum| sum.is_finite())
+                }
+
+                fn to_f64(self) -> f64 {
+                    self.into()
+                }
+
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_number_float!(f32, f64);
+
+#[derive(Debug, Clone, Copy)]
+pub struct SliceStats<'a, T> {
+    slice: &'a [T],
+}
+
+impl<'a, T: Number> SliceStats<'a, T> {
+    pub fn new(slice: &'a [T]) -> Self {
+        SliceStats { slice }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slice.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.slice.iter().copied().min_by(T::total_cmp)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.slice.iter().copied().max_by(T::total_cmp)
+    }
+
+    // `None` on overflow, the sum of an empty slice is `0`
+    pub fn sum(&self) -> Option<T>
+    where
+        T: Default,
+    {
+        self.slice
+            .iter()
+            .try_fold(T::default(), |sum, &x| sum.checked_add(x))
+    }
+
+    // Calculated in `f64`, so it can't overflow
+    pub fn mean(&self) -> Option<f64> {
+        self.mean_and_variance().map(|(mean, _)| mean)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+
+    // Linear interpolation between the closest ranks, `p` in `[0, 100]`
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+
+        let rank = p / 100.0 * (self.len() - 1) as f64;
+        let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+
+        let low_value = self.nth_smallest(low)?.to_f64();
+        if low == high {
+            return Some(low_value);
+        }
+        let high_value = self.nth_smallest(high)?.to_f64();
+
+        Some(low_value + (high_value - low_value) * (rank - low as f64))
+    }
+
+    // The `k`-th smallest element, `0` is the minimum
+    pub fn nth_smallest(&self, k: usize) -> Option<T> {
+        if k >= self.len() {
+            return None;
+        }
+
+        Some(select(self.slice, k))
+    }
+
+    // The most frequent element, the smallest one on ties
+    // NOTE: sorts a copy of the slice, as floats can't be hashed
+    pub fn mode(&self) -> Option<T> {
+        let mut sorted = self.slice.to_vec();
+        sorted.sort_by(T::total_cmp);
+
+        let mut best: Option<(T, usize)> = None;
+        for run in sorted.chunk_by(|a, b| a.total_cmp(b) == Ordering::Equal) {
+            if best.is_none_or(|(_, count)| run.len() > count) {
+                best = Some((run[0], run.len()));
+            }
+        }
+
+        best.map(|(value, _)| value)
+    }
+
+    // Population variance
+    pub fn variance(&self) -> Option<f64> {
+        self.mean_and_variance().map(|(_, variance)| variance)
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    // Welford's online algorithm, numerically stable in a single pass
+    fn mean_and_variance(&self) -> Option<(f64, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (mut mean, mut m2) = (0.0, 0.0);
+        for (i, x) in self.slice.iter().enumerate() {
+            let x = x.to_f64();
+            let delta = x - mean;
+            mean += delta / (i + 1) as f64;
+            m2 += delta * (x - mean);
+        }
+
+        Some((mean, m2 / self.len() as f64))
+    }
+
+    // `bins` equal ranges between the minimum and the maximum
+    pub fn histogram(&self, bins: usize) -> Histogram {
+        let bins = bins.max(1);
+        let (min, max) = match (self.min(), self.max()) {
+            (Some(min), Some(max)) => (min.to_f64(), max.to_f64()),
+            _ => (0.0, 0.0),
+        };
+        let width = (max - min) / bins as f64;
+
+        let mut counts = vec![0; bins];
+        for x in self.slice {
+            let bin = if width > 0.0 {
+                ((x.to_f64() - min) / width) as usize
+            } else {
+                0
+            };
+
+            // The maximum belongs to the last bin
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        Histogram { min, width, counts }
+    }
+}
+
+// The report, `{:.N}` sets the precision of the calculated values
+impl<T: Number + Default> fmt::Display for SliceStats<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        let show = |value: Option<f64>| match value {
+            Some(value) => format!("{:.*}", precision, value),
+            None => "-".to_owned(),
+        };
+        let show_t = |value: Option<T>| match value {
+            Some(value) => value.to_string(),
+            None => "-".to_owned(),
+        };
+
+        writeln!(f, "len:      {}", self.len())?;
+        writeln!(f, "min:      {}", show_t(self.min()))?;
+        writeln!(f, "max:      {}", show_t(self.max()))?;
+        match self.sum() {
+            Some(sum) => writeln!(f, "sum:      {}", sum)?,
+            None => writeln!(f, "sum:      overflow")?,
+        }
+        writeln!(f, "mean:     {}", show(self.mean()))?;
+        writeln!(f, "median:   {}", show(self.median()))?;
+        writeln!(f, "mode:     {}", show_t(self.mode()))?;
+        writeln!(f, "variance: {}", show(self.variance()))?;
+        writeln!(f, "std dev:  {}", show(self.std_dev()))?;
+        write!(
+            f,
+            "p25/p75/p90: {} / {} / {}",
+            show(self.percentile(25.0)),
+            show(self.percentile(75.0)),
+            show(self.percentile(90.0))
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    min: f64,
+    // Of every bin, `0` if all the values are equal
+    width: f64,
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+}
+
+// One line per bin, `{:.N}` sets the precision of the bin edges:
+//   [  1.0,   2.0) ######## 2
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(1);
+        let peak = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let edge = |i: usize| format!("{:.*}", precision, self.min + self.width * i as f64);
+        let edge_width = (0..=self.counts.len())
+            .map(|i| edge(i).len())
+            .max()
+            .unwrap_or(0);
+
+        for (i, &count) in self.counts.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+
+            let last = i + 1 == self.counts.len();
+            write!(
+                f,
+                "[{:>w$}, {:>w$}{} {} {}",
+                edge(i),
+                edge(i + 1),
+                if last { ']' } else { ')' },
+                "#".repeat((count * MAX_BAR).div_ceil(peak)),
+                count,
+                w = edge_width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Quickselect without moving the elements: the candidates are the elements
+// strictly between `low` and `high`, each step picks a pivot among them and
+// keeps the side that holds the `k`-th one
+fn select<T: Number>(slice: &[T], mut k: usize) -> T {
+    let mut low: Option<T> = None;
+    let mut high: Option<T> = None;
+    // Deterministic xorshift, the pivots only need to be spread out
+    let mut state = 0x2545_f491_4f6c_dd1d_u64 ^ k as u64;
+
+    loop {
+        let candidates = || {
+            slice.iter().copied().filter(move |x| {
+                low.is_none_or(|low| x.total_cmp(&low) == Ordering::Greater)
+                    && high.is_none_or(|high| x.total_cmp(&high) == Ordering::Less)
+            })
+        };
+
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let count = candidates().count();
+        let pivot = candidates()
+            .nth((state % count as u64) as usize)
+            .expect("the k-th element is always a candidate");
+
+        let (mut less, mut equal) = (0, 0);
+        for x in candidates() {
+            match x.total_cmp(&pivot) {
+                Ordering::Less => less += 1,
+                Ordering::Equal => equal += 1,
+                Ordering::Greater => {}
+            }
+        }
+
+        if k < less {
+            high = Some(pivot);
+        } else if k < less + equal {
+            return pivot;
+        } else {
+            k -= less + equal;
+            low = Some(pivot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let stats = SliceStats::<i32>::new(&[]);
+
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+        assert_eq!(stats.sum(), Some(0));
+        assert_eq!(stats.mean(), None);
+        assert_eq!(stats.median(), None);
+        assert_eq!(stats.mode(), None);
+        assert_eq!(stats.variance(), None);
+        assert_eq!(stats.percentile(10.0), None);
+        assert_eq!(stats.histogram(3).counts(), &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_one_element() {
+        let stats = SliceStats::new(&[7u8]);
+
+        assert_eq!((stats.min(), stats.max()), (Some(7), Some(7)));
+        assert_eq!(stats.sum(), Some(7));
+        assert_eq!(stats.mean(), Some(7.0));
+        assert_eq!(stats.median(), Some(7.0));
+        assert_eq!(stats.percentile(99.0), Some(7.0));
+        assert_eq!(stats.mode(), Some(7));
+        assert_eq!(stats.std_dev(), Some(0.0));
+    }
+
+    #[test]
+    fn test_all_equal() {
+        let stats = SliceStats::new(&[0.5; 10]);
+
+        assert_eq!(stats.median(), Some(0.5));
+        assert_eq!(stats.variance(), Some(0.0));
+        assert_eq!(stats.mode(), Some(0.5));
+        assert_eq!(stats.histogram(4).counts(), &[10, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(SliceStats::new(&[100i8, 27]).sum(), Some(127));
+        assert_eq!(SliceStats::new(&[100i8, 28]).sum(), None);
+        assert_eq!(SliceStats::new(&[f64::MAX, f64::MAX]).sum(), None);
+        // The mean is calculated in `f64`
+        assert_eq!(SliceStats::new(&[100i8, 28]).mean(), Some(64.0));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let slice = [3, -1, 4, 1, -5, 9, 2, 6, 5, 3, 5];
+        let stats = SliceStats::new(&slice);
+
+        assert_eq!((stats.min(), stats.max()), (Some(-5), Some(9)));
+        assert_eq!(stats.sum(), Some(32));
+        assert_eq!(stats.median(), Some(3.0));
+        assert_eq!(stats.percentile(0.0), Some(-5.0));
+        assert_eq!(stats.percentile(100.0), Some(9.0));
+        assert_eq!(stats.percentile(25.0), Some(1.5));
+        assert_eq!(stats.percentile(101.0), None);
+        // 3 and 5 appear twice
+        assert_eq!(stats.mode(), Some(3));
+
+        let mean = 32.0 / 11.0;
+        let variance = slice
+            .iter()
+            .map(|&x| (x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / 11.0;
+        assert!((stats.mean().unwrap() - mean).abs() < 1e-12);
+        assert!((stats.variance().unwrap() - variance).abs() < 1e-12);
+
+        assert_eq!(SliceStats::new(&[1, 2, 3, 4]).median(), Some(2.5));
+    }
+
+    // Every rank against a sorted copy, with many duplicates
+    #[test]
+    fn test_nth_smallest() {
+        let slice: Vec<i64> = (0..500).map(|i| (i * 7919) % 61 - 30).collect();
+        let mut sorted = slice.clone();
+        sorted.sort();
+
+        let stats = SliceStats::new(&slice);
+        for (k, &expected) in sorted.iter().enumerate() {
+            assert_eq!(stats.nth_smallest(k), Some(expected));
+        }
+        assert_eq!(stats.nth_smallest(slice.len()), None);
+    }
+
+    #[test]
+    fn test_nan() {
+        let stats = SliceStats::new(&[1.0, f64::NAN, -2.0]);
+
+        assert_eq!(stats.min(), Some(-2.0));
+        assert!(stats.max().unwrap().is_nan());
+        assert_eq!(stats.median(), Some(1.0));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = SliceStats::new(&[1, 1, 1, 2, 2, 2, 3, 3, 3, 4]).histogram(3);
+
+        assert_eq!(histogram.counts(), &[3, 3, 4]);
+        assert_eq!(
+            histogram.to_string(),
+            "\
+[1.0, 2.0) ############################## 3
+[2.0, 3.0) ############################## 3
+[3.0, 4.0] ######################################## 4"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 2.2 `reverse(pair: (i32, bool))` for every tuple of 1 to 12
+//       elements, with a few more helpers:
+//         (1, true, 'a').reverse()       // ('a', true, 1)
+//         (1, true).push_back('a')       // (1, true, 'a')
+//         (1, 2, 3).map_all(|x| x * 2)   // (2, 4, 6)
+//         (1, 2, 3).to_array()           // [1, 2, 3]
+//         (1, true).display()            // (1, true)
+//       `Display` can't be implemented for tuples outside of `std`, so it
+//       goes through `display()`, as `Path::display()` does
+
+use std::fmt;
+
+pub trait Tuple: Sized {
+    const LEN: usize;
+
+    type Reversed;
+    type PushFront<T>;
+    type PushBack<T>;
+
+    fn reverse(self) -> Self::Reversed;
+
+    fn push_front<T>(self, value: T) -> Self::PushFront<T>;
+
+    fn push_back<T>(self, value: T) -> Self::PushBack<T>;
+
+    // `Display` when every element is `Display`
+    fn display(&self) -> TupleDisplay<'_, Self> {
+        TupleDisplay(self)
+    }
+}
+
+// Tuples of `N` elements of the same type `T`
+pub trait Homogeneous<T, const N: usize>: Tuple {
+    type Map<U>;
+
+    // The elements are mapped from the first one
+    fn map_all<U, F: FnMut(T) -> U>(self, f: F) -> Self::Map<U>;
+
+    fn to_array(self) -> [T; N];
+}
+
+pub struct TupleDisplay<'a, T>(&'a T);
+
+// `$t` written as `$with`, to repeat a type once per element
+macro_rules! replace {
+    ($t:ident, $with:ty) => {
+        $with
+    };
+}
+
+macro_rules! impl_tuple {
+    ($($len:literal: ($($t:ident $v:ident),+) reversed ($($rt:ident $rv:ident),+);)+) => {
+        $(
+            impl<$($t),+> Tuple for ($($t,)+) {
+                const LEN: usize = $len;
+
+                type Reversed = ($($rt,)+);
+                type PushFront<T> = (T, $($t,)+);
+                type PushBack<T> = ($($t,)+ T,);
+
+                fn reverse(self) -> Self::Reversed {
+                    let ($($v,)+) = self;
+                    ($($rv,)+)
+                }
+
+                fn push_front<T>(self, value: T) -> Self::PushFront<T> {
+                    let ($($v,)+) = self;
+                    (value, $($v,)+)
+                }
+
+                fn push_back<T>(self, value: T) -> Self::PushBack<T> {
+                    let ($($v,)+) = self;
+                    ($($v,)+ value,)
+                }
+            }
+
+            impl<T> Homogeneous<T, $len> for ($(replace!($t, T),)+) {
+                type Map<U> = ($(replace!($t, U),)+);
+
+                fn map_all<U, F: FnMut(T) -> U>(self, mut f: F) -> Self::Map<U> {
+                    let ($($v,)+) = self;
+                    ($(f($v),)+)
+                }
+
+                fn to_array(self) -> [T; $len] {
+                    let ($($v,)+) = self;
+                    [$($v),+]
+                }
+            }
+
+            // The flags, e.g. `{:.1}`, apply to every element, a single
+            // element is written `(a,)` as `Debug` does
+            impl<$($t: fmt::Display),+> fmt::Display for TupleDisplay<'_, ($($t,)+)> {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    let ($($v,)+) = self.0;
+                    let elements: [&dyn fmt::Display; $len] = [$($v),+];
+
+                    write!(f, "(")?;
+                    for (i, element) in elements.iter().enumerate() {
+                        if i != 0 {
+                            write!(f, ", ")?;
+                        }
+                        element.fmt(f)?;
+                    }
+                    if $len == 1 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, ")")
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple! {
+    1: (A a) reversed (A a);
+    2: (A a, B b) reversed (B b, A a);
+    3: (A a, B b, C c) reversed (C c, B b, A a);
+    4: (A a, B b, C c, D d) reversed (D d, C c, B b, A a);
+    5: (A a, B b, C c, D d, E e) reversed (E e, D d, C c, B b, A a);
+    6: (A a, B b, C c, D d, E e, F f) reversed (F f, E e, D d, C c, B b, A a);
+    7: (A a, B b, C c, D d, E e, F f, G g) reversed (G g, F f, E e, D d, C c, B b, A a);
+    8: (A a, B b, C c, D d, E e, F f, G g, H h)
+        reversed (H h, G g, F f, E e, D d, C c, B b, A a);
+    9: (A a, B b, C c, D d, E e, F f, G g, H h, I i)
+        reversed (I i, H h, G g, F f, E e, D d, C c, B b, A a);
+    10: (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j)
+        reversed (J j, I i, H h, G g, F f, E e, D d, C c, B b, A a);
+    11: (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k)
+        reversed (K k, J j, I i, H h, G g, F f, E e, D d, C c, B b, A a);
+    12: (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l)
+        reversed (L l, K k, J j, I i, H h, G g, F f, E e, D d, C c, B b, A a);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The `long_tuple` of 2.2
+    const LONG_TUPLE: (u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, char, bool) = (
+        1u8, 2u16, 3u32, 4u64, -1i8, -2i16, -3i32, -4i64, 0.1f32, 0.2f64, 'a', true,
+    );
+
+    #[test]
+    fn test_reverse() {
+        assert_eq!((1, true).reverse(), (true, 1));
+        assert_eq!((5u32,).reverse(), (5u32,));
+        assert_eq!(
+            LONG_TUPLE.reverse(),
+            (true, 'a', 0.2f64, 0.1f32, -4i64, -3i32, -2i16, -1i8, 4u64, 3u32, 2u16, 1u8)
+        );
+        assert_eq!(LONG_TUPLE.reverse().reverse(), LONG_TUPLE);
+    }
+
+    #[test]
+    fn test_push() {
+        assert_eq!((1,).push_front("a"), ("a", 1));
+        assert_eq!((1, 2).push_back(3.0), (1, 2, 3.0));
+        assert_eq!(<(i32, bool) as Tuple>::LEN, 2);
+        assert_eq!(
+            <(i32, i32, i32, i32, i32, i32, i32, i32, i32, i32, i32) as Tuple>::LEN,
+            11
+        );
+
+        // The 13 elements of the 2.2 `too_long_tuple`, which is fine to build
+        let too_long_tuple = LONG_TUPLE.push_back(13);
+        assert_eq!(too_long_tuple.0, 1u8);
+        assert_eq!(too_long_tuple.12, 13);
+        assert_eq!(LONG_TUPLE.push_front(0).11, 'a');
+    }
+
+    #[test]
+    fn test_homogeneous() {
+        assert_eq!((1, 2, 3).map_all(|x| x * 2), (2, 4, 6));
+        assert_eq!(("a", "bc").map_all(str::len), (1, 2));
+        assert_eq!((1, 2, 3).to_array(), [1, 2, 3]);
+
+        let twelve = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12);
+        assert_eq!(twelve.to_array().iter().sum::<i32>(), 78);
+
+        // Mapped in order
+        let mut calls = Vec::new();
+        let squares = twelve.map_all(|x| {
+            calls.push(x);
+            x * x
+        });
+        assert_eq!(squares.11, 144);
+        assert_eq!(calls, twelve.to_array());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            (1, "hello", 4.5, true).display().to_string(),
+            "(1, hello, 4.5, true)"
+        );
+        assert_eq!((5u32,).display().to_string(), "(5,)");
+        assert_eq!(format!("{:.2}", (1.0, 0.5).display()), "(1.00, 0.50)");
+        assert_eq!(
+            LONG_TUPLE.display().to_string(),
+            "(1, 2, 3, 4, -1, -2, -3, -4, 0.1, 0.2, a, true)"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// Decodes recorded terminal input into `WebEvent`s, from a file or stdin:
+// `cargo run --manifest-path ./03_custom_types/Cargo.toml --bin web_events -- input.txt`
+// `printf 'x\e[<0;20;80M' | cargo run --manifest-path ./03_custom_types/Cargo.toml --bin web_events`
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+use _3_custom_types::web_event::{DecodeError, Decoder};
+
+// Prints one event or error per line, and stops at the first I/O error
+fn print_events<R: BufRead>(reader: R) -> Result<(), io::Error> {
+    for event in Decoder::new(reader) {
+        match event {
+            Ok(event) => println!("{:?}", event),
+            Err(DecodeError::Io(e)) => return Err(e),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.as_slice() {
+        [] => print_events(io::stdin().lock()),
+        [path] if path == "-" => print_events(io::stdin().lock()),
+        [path] => File::open(path).and_then(|file| print_events(BufReader::new(file))),
+        _ => {
+            eprintln!("Usage: web_events [FILE]");
+            eprintln!("Reads the standard input without FILE, or when FILE is -");
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
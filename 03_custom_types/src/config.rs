@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 3.3 constants are the defaults of a runtime configuration, each
+//       value can be overridden by, in order of precedence (the last wins):
+//         1. environment variables: `CUSTOM_TYPES_LANGUAGE`, `CUSTOM_TYPES_THRESHOLD`
+//         2. a `key = value` file passed with `--config <path>`
+//         3. command-line flags: `--language <value>`, `--threshold <value>`
+//       `--print-config` shows each value and where it came from, then exits
+//       An invalid configuration stops the chapter before it starts
+//       The environment variables are prefixed because `LANGUAGE` is
+//       already used for the locale on most systems
+
+use std::error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+// Globals are declared outside all other scopes.
+pub static LANGUAGE: &str = "Rust";
+pub const THRESHOLD: i32 = 10;
+
+const ENV_PREFIX: &str = "CUSTOM_TYPES_";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    Env(String),
+    File { path: String, line: usize },
+    Flag(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Env(name) => write!(f, "environment variable {}", name),
+            Source::File { path, line } => write!(f, "{}:{}", path, line),
+            Source::Flag(flag) => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    InvalidValue {
+        key: &'static str,
+        value: String,
+        source: Source,
+    },
+    UnknownKey {
+        key: String,
+        source: Source,
+    },
+    // A config file line without `=`
+    Syntax {
+        path: String,
+        line: usize,
+    },
+    MissingValue(String),
+    UnknownFlag(String),
+    Io {
+        path: String,
+        err: io::Error,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidValue { key, value, source } => {
+                write!(f, "invalid {} `{}` ({})", key, value, source)
+            }
+            ConfigError::UnknownKey { key, source } => {
+                write!(f, "unknown key `{}` ({})", key, source)
+            }
+            ConfigError::Syntax { path, line } => {
+                write!(f, "expected `key = value` ({}:{})", path, line)
+            }
+            ConfigError::MissingValue(flag) => write!(f, "missing value for {}", flag),
+            ConfigError::UnknownFlag(flag) => write!(f, "unknown flag {}", flag),
+            ConfigError::Io { path, .. } => write!(f, "couldn't read {}", path),
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigError::Io { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Command-line flags of the chapter binary
+#[derive(Debug, Default)]
+pub struct Args {
+    pub print_config: bool,
+    pub config_path: Option<String>,
+    // `(flag, value)` pairs in the order of appearance
+    overrides: Vec<(String, String)>,
+}
+
+impl Args {
+    // Accepts both `--flag value` and `--flag=value`
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, ConfigError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--print-config" {
+                parsed.print_config = true;
+                continue;
+            }
+
+            let (flag, value) = match arg.find('=') {
+                Some(index) => (arg[..index].to_owned(), Some(arg[index + 1..].to_owned())),
+                None => (arg, None),
+            };
+
+            if !matches!(flag.as_str(), "--config" | "--language" | "--threshold") {
+                return Err(ConfigError::UnknownFlag(flag));
+            }
+
+            let value = match value.or_else(|| args.next()) {
+                Some(value) => value,
+                None => return Err(ConfigError::MissingValue(flag)),
+            };
+
+            if flag == "--config" {
+                parsed.config_path = Some(value);
+            } else {
+                parsed.overrides.push((flag, value));
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub language: Setting<String>,
+    pub threshold: Setting<i32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            language: Setting {
+                value: LANGUAGE.to_owned(),
+                source: Source::Default,
+            },
+            threshold: Setting {
+                value: THRESHOLD,
+                source: Source::Default,
+            },
+        }
+    }
+}
+
+impl Config {
+    // Apply every layer on top of the defaults, `vars` is usually
+    // `env::vars_os()`
+    pub fn load<I>(args: &Args, vars: I) -> Result<Config, ConfigError>
+    where
+        I: IntoIterator<Item = (OsString, OsString)>,
+    {
+        let mut config = Config::default();
+
+        config.apply_env(vars)?;
+
+        if let Some(path) = &args.config_path {
+            let contents = fs::read_to_string(path).map_err(|err| ConfigError::Io {
+                path: path.clone(),
+                err,
+            })?;
+
+            config.apply_file(path, &contents)?;
+        }
+
+        config.apply_args(args)?;
+
+        Ok(config)
+    }
+
+    // Variables are `OsString`s, as `env::vars` panics on any variable that
+    // is not valid UTF-8, even unrelated ones, which are skipped here
+    pub fn apply_env<I: IntoIterator<Item = (OsString, OsString)>>(
+        &mut self,
+        vars: I,
+    ) -> Result<(), ConfigError> {
+        for (name, value) in vars {
+            let name = match name.into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            let key = match name.strip_prefix(ENV_PREFIX) {
+                Some("LANGUAGE") => "language",
+                Some("THRESHOLD") => "threshold",
+                _ => continue,
+            };
+            let source = Source::Env(name.clone());
+
+            match value.into_string() {
+                Ok(value) => self.set(key, &value, source)?,
+                Err(value) => {
+                    return Err(ConfigError::InvalidValue {
+                        key,
+                        value: value.to_string_lossy().into_owned(),
+                        source,
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Lines are `key = value`, blank lines and lines starting with `#` are
+    // skipped
+    pub fn apply_file(&mut self, path: &str, contents: &str) -> Result<(), ConfigError> {
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let source = Source::File {
+                path: path.to_owned(),
+                line: index + 1,
+            };
+
+            match line.find('=') {
+                Some(eq) => self.set(line[..eq].trim(), line[eq + 1..].trim(), source)?,
+                None => {
+                    return Err(ConfigError::Syntax {
+                        path: path.to_owned(),
+                        line: index + 1,
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn apply_args(&mut self, args: &Args) -> Result<(), ConfigError> {
+        for (flag, value) in &args.overrides {
+            self.set(
+                flag.trim_start_matches("--"),
+                value,
+                Source::Flag(flag.clone()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str, source: Source) -> Result<(), ConfigError> {
+        match key {
+            "language" => {
+                self.language = Setting {
+                    value: value.to_owned(),
+                    source,
+                }
+            }
+            "threshold" => {
+                let value = value.parse().map_err(|_| ConfigError::InvalidValue {
+                    key: "threshold",
+                    value: value.to_owned(),
+                    source: source.clone(),
+                })?;
+
+                self.threshold = Setting { value, source }
+            }
+            _ => {
+                return Err(ConfigError::UnknownKey {
+                    key: key.to_owned(),
+                    source,
+                })
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The `--print-config` report
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "language = {} ({})",
+            self.language.value, self.language.source
+        )?;
+        write!(
+            f,
+            "threshold = {} ({})",
+            self.threshold.value, self.threshold.source
+        )
+    }
+}
+
+static EFFECTIVE: OnceLock<Config> = OnceLock::new();
+
+// Make `config` the effective configuration, only the first call has effect
+pub fn init(config: Config) -> &'static Config {
+    EFFECTIVE.get_or_init(|| config)
+}
+
+// The effective configuration, the defaults until `init` is called
+pub fn effective() -> &'static Config {
+    EFFECTIVE.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, ConfigError> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        vars.iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::default();
+
+        assert_eq!(config.language.value, LANGUAGE);
+        assert_eq!(config.threshold.value, THRESHOLD);
+        assert_eq!(
+            config.to_string(),
+            "language = Rust (default)\nthreshold = 10 (default)"
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let mut config = Config::default();
+
+        // Environment, then the file, then the flags
+        config
+            .apply_env(env(&[
+                ("CUSTOM_TYPES_LANGUAGE", "C"),
+                ("CUSTOM_TYPES_THRESHOLD", "1"),
+                ("LANGUAGE", "en_US"),
+            ]))
+            .unwrap();
+        assert_eq!(config.language.value, "C");
+        assert_eq!(config.threshold.value, 1);
+
+        config
+            .apply_file("a.conf", "# comment\n\nlanguage = Go\nthreshold=2\n")
+            .unwrap();
+        assert_eq!(config.language.value, "Go");
+        assert_eq!(config.threshold.value, 2);
+
+        config
+            .apply_args(&args(&["--threshold", "3"]).unwrap())
+            .unwrap();
+        assert_eq!(config.language.value, "Go");
+        assert_eq!(config.threshold.value, 3);
+
+        assert_eq!(
+            config.language.source,
+            Source::File {
+                path: "a.conf".to_owned(),
+                line: 3
+            }
+        );
+        assert_eq!(
+            config.threshold.source,
+            Source::Flag("--threshold".to_owned())
+        );
+        assert_eq!(
+            config.to_string(),
+            "language = Go (a.conf:3)\nthreshold = 3 (flag --threshold)"
+        );
+
+        // Within a layer the last value wins
+        config
+            .apply_args(&args(&["--language=Zig", "--language", "Rust"]).unwrap())
+            .unwrap();
+        assert_eq!(config.language.value, "Rust");
+    }
+
+    #[test]
+    fn test_args() {
+        let parsed = args(&["--print-config", "--config=a.conf", "--threshold", "-5"]).unwrap();
+
+        assert!(parsed.print_config);
+        assert_eq!(parsed.config_path.as_deref(), Some("a.conf"));
+        assert_eq!(
+            parsed.overrides,
+            vec![("--threshold".to_owned(), "-5".to_owned())]
+        );
+        assert!(!args(&[]).unwrap().print_config);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |result: Result<(), ConfigError>| result.unwrap_err().to_string();
+
+        assert_eq!(
+            args(&["--verbose"]).unwrap_err().to_string(),
+            "unknown flag --verbose"
+        );
+        assert_eq!(
+            args(&["--language"]).unwrap_err().to_string(),
+            "missing value for --language"
+        );
+
+        let mut config = Config::default();
+        assert_eq!(
+            error(config.apply_env(env(&[("CUSTOM_TYPES_THRESHOLD", "ten")]))),
+            "invalid threshold `ten` (environment variable CUSTOM_TYPES_THRESHOLD)"
+        );
+        assert_eq!(
+            error(config.apply_file("a.conf", "language = C\nthreshold")),
+            "expected `key = value` (a.conf:2)"
+        );
+        assert_eq!(
+            error(config.apply_file("a.conf", "colour = red")),
+            "unknown key `colour` (a.conf:1)"
+        );
+        assert_eq!(
+            error(config.apply_args(&args(&["--threshold", "1.5"]).unwrap())),
+            "invalid threshold `1.5` (flag --threshold)"
+        );
+
+        let missing = args(&["--config", "/nonexistent/custom_types.conf"]).unwrap();
+        assert!(matches!(
+            Config::load(&missing, env(&[])),
+            Err(ConfigError::Io { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_env() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let invalid = || OsString::from_vec(vec![b'a', 0xff]);
+
+        // Unrelated variables are skipped, whatever their name or value
+        let mut vars = env(&[("CUSTOM_TYPES_THRESHOLD", "4")]);
+        vars.push((invalid(), "x".into()));
+        vars.push(("PATH".into(), invalid()));
+
+        let config = Config::load(&args(&[]).unwrap(), vars).unwrap();
+        assert_eq!(config.threshold.value, 4);
+
+        let mut config = Config::default();
+        assert_eq!(
+            config
+                .apply_env(vec![("CUSTOM_TYPES_LANGUAGE".into(), invalid())])
+                .unwrap_err()
+                .to_string(),
+            "invalid language `a\u{fffd}` (environment variable CUSTOM_TYPES_LANGUAGE)"
+        );
+    }
+}
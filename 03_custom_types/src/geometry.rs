@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 3.1 `Point` and `Rectangle` moved here and grown into an
+//       axis-aligned geometry API
+//       `y` grows downwards, so `top_left` holds the smallest coordinates.
+//       The fields stay public for destructuring, every method works on the
+//       normalized corners, so they may come in any order
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+// Structs can be reused as fields of another struct
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    // A rectangle can be specified by where the top left and bottom right
+    // corners are in space.
+    pub top_left: Point,
+    pub bottom_right: Point,
+}
+
+impl Rectangle {
+    // Create a rectangle from any two opposite corners
+    pub fn new(a: Point, b: Point) -> Rectangle {
+        Rectangle {
+            top_left: Point {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+            },
+            bottom_right: Point {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+            },
+        }
+    }
+
+    // A square with one corner on `point`, a negative `side` extends it to
+    // the left and up
+    pub fn square(point: Point, side: f32) -> Rectangle {
+        Rectangle::new(
+            point,
+            Point {
+                x: point.x + side,
+                y: point.y + side,
+            },
+        )
+    }
+
+    pub fn normalized(&self) -> Rectangle {
+        Rectangle::new(self.top_left, self.bottom_right)
+    }
+
+    pub fn left(&self) -> f32 {
+        self.top_left.x.min(self.bottom_right.x)
+    }
+
+    pub fn right(&self) -> f32 {
+        self.top_left.x.max(self.bottom_right.x)
+    }
+
+    pub fn top(&self) -> f32 {
+        self.top_left.y.min(self.bottom_right.y)
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.top_left.y.max(self.bottom_right.y)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right() - self.left()
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bottom() - self.top()
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    pub fn center(&self) -> Point {
+        Point {
+            x: (self.left() + self.right()) / 2.0,
+            y: (self.top() + self.bottom()) / 2.0,
+        }
+    }
+
+    // Edges belong to the rectangle
+    pub fn contains(&self, point: Point) -> bool {
+        (self.left()..=self.right()).contains(&point.x)
+            && (self.top()..=self.bottom()).contains(&point.y)
+    }
+
+    // Rectangles that only touch by an edge or a corner intersect too
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.left() <= other.right()
+            && other.left() <= self.right()
+            && self.top() <= other.bottom()
+            && other.top() <= self.bottom()
+    }
+
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        Some(Rectangle {
+            top_left: Point {
+                x: self.left().max(other.left()),
+                y: self.top().max(other.top()),
+            },
+            bottom_right: Point {
+                x: self.right().min(other.right()),
+                y: self.bottom().min(other.bottom()),
+            },
+        })
+    }
+
+    // The smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        Rectangle {
+            top_left: Point {
+                x: self.left().min(other.left()),
+                y: self.top().min(other.top()),
+            },
+            bottom_right: Point {
+                x: self.right().max(other.right()),
+                y: self.bottom().max(other.bottom()),
+            },
+        }
+    }
+
+    pub fn translate(&self, dx: f32, dy: f32) -> Rectangle {
+        let Rectangle {
+            top_left,
+            bottom_right,
+        } = self.normalized();
+
+        Rectangle {
+            top_left: Point {
+                x: top_left.x + dx,
+                y: top_left.y + dy,
+            },
+            bottom_right: Point {
+                x: bottom_right.x + dx,
+                y: bottom_right.y + dy,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic xorshift generator, enough to sample arbitrary rectangles
+    struct Rng(u32);
+
+    impl Rng {
+        fn next_coordinate(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+
+            // Small integers produce plenty of overlapping and touching cases
+            (self.0 % 41) as f32 - 20.0
+        }
+
+        fn next_rectangle(&mut self) -> Rectangle {
+            // Corners are not ordered on purpose
+            Rectangle {
+                top_left: Point {
+                    x: self.next_coordinate(),
+                    y: self.next_coordinate(),
+                },
+                bottom_right: Point {
+                    x: self.next_coordinate(),
+                    y: self.next_coordinate(),
+                },
+            }
+        }
+    }
+
+    const SAMPLES: usize = 10_000;
+
+    #[test]
+    fn test_intersection_is_commutative() {
+        let mut rng = Rng(0x2545_f491);
+
+        for _ in 0..SAMPLES {
+            let (a, b) = (rng.next_rectangle(), rng.next_rectangle());
+
+            assert_eq!(a.intersects(&b), b.intersects(&a));
+            assert_eq!(a.intersection(&b), b.intersection(&a), "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_union_is_commutative() {
+        let mut rng = Rng(0x9e37_79b9);
+
+        for _ in 0..SAMPLES {
+            let (a, b) = (rng.next_rectangle(), rng.next_rectangle());
+
+            assert_eq!(a.union(&b), b.union(&a), "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_intersection_area_never_exceeds_operands() {
+        let mut rng = Rng(0x1234_5678);
+
+        for _ in 0..SAMPLES {
+            let (a, b) = (rng.next_rectangle(), rng.next_rectangle());
+
+            if let Some(c) = a.intersection(&b) {
+                assert!(c.area() <= a.area() && c.area() <= b.area());
+                assert!(a.contains(c.center()) && b.contains(c.center()));
+            }
+
+            let u = a.union(&b);
+            assert!(u.area() >= a.area() && u.area() >= b.area());
+        }
+    }
+
+    #[test]
+    fn test_unordered_corners() {
+        let rect = Rectangle {
+            top_left: Point { x: 3.0, y: 5.0 },
+            bottom_right: Point { x: 1.0, y: 2.0 },
+        };
+
+        assert_eq!(rect.width(), 2.0);
+        assert_eq!(rect.height(), 3.0);
+        assert_eq!(rect.area(), 6.0);
+        assert_eq!(rect.center(), Point { x: 2.0, y: 3.5 });
+        assert!(rect.contains(Point { x: 1.0, y: 5.0 }));
+        assert_eq!(
+            rect.translate(1.0, -1.0),
+            Rectangle::new(Point { x: 2.0, y: 1.0 }, Point { x: 4.0, y: 4.0 })
+        );
+        assert_eq!(
+            Rectangle::square(Point { x: 1.0, y: 1.0 }, -1.0),
+            Rectangle::new(Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 })
+        );
+    }
+}
@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 3.2.3 `List` moved here from `main.rs` and made generic over the
+//       element type, so it can hold strings and structs, not only `u32`
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use List::*;
+
+#[derive(Default)]
+pub enum List<T> {
+    // Cons: Tuple struct that wraps an element and a pointer to the next node
+    Cons(T, Link<T>),
+    // Nil: A node that signifies the end of the linked list
+    #[default]
+    Nil,
+}
+
+// Methods can be attached to an enum
+impl<T> List<T> {
+    // Create an empty list
+    pub fn new() -> List<T> {
+        // `Nil` has type `List`
+        Nil
+    }
+
+    // Consume a list, and return the same list with a new element at its front
+    pub fn prepend(self, elem: T) -> List<T> {
+        // `Cons` also has type List
+        Cons(elem, Link::new(self))
+    }
+
+    // Return the length of the list
+    pub fn len(&self) -> usize {
+        // `self` has to be matched, because the behavior of this method
+        // depends on the variant of `self`
+        // `self` has type `&List`, and `*self` has type `List`, matching on a
+        // concrete type `T` is preferred over a match on a reference `&T`
+        // after Rust 2018 you can use self here and tail (with no ref) below as well,
+        // rust will infer &s and ref tail.
+        // See https://doc.rust-lang.org/edition-guide/rust-2018/ownership-and-lifetimes/default-match-bindings.html
+        // match *self {
+        //     // Can't take ownership of the tail, because `self` is borrowed;
+        //     // instead take a reference to the tail
+        //     Cons(_, ref tail) => 1 + tail.len(),
+        //     // Base Case: An empty list has zero length
+        //     Nil => 0,
+        // }
+
+        // NOTE: Rust 2018 variant (*self -> self, ref tail -> tail):
+        // match self {
+        //     Cons(_, tail) => 1 + tail.len(),
+        //     Nil => 0,
+        // }
+
+        // NOTE: iterative, the recursive `1 + tail.len()` overflows the stack
+        //       on long lists
+        //       Algorithm complexity: O(n)
+        self.iter().count()
+    }
+
+    // Return representation of the list as a (heap allocated) string
+    pub fn stringify(&self) -> String
+    where
+        T: fmt::Display,
+    {
+        // match *self {
+        //     Cons(head, ref tail) => {
+        //         // `format!` is similar to `print!`, but returns a heap
+        //         // allocated string instead of printing to the console
+        //         format!("{}, {}", head, tail.stringify())
+        //     }
+        //     Nil => {
+        //         format!("Nil")
+        //     }
+        // }
+
+        // NOTE: the recursive `format!` moved to the `Display` impl below,
+        //       `format!` is similar to `print!`, but returns a heap
+        //       allocated string instead of printing to the console
+        format!("{}", self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Nil)
+    }
+
+    // Borrow the first element, if any
+    pub fn peek(&self) -> Option<&T> {
+        match self {
+            Cons(head, _) => Some(head),
+            Nil => None,
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Cons(head, _) => Some(head),
+            Nil => None,
+        }
+    }
+
+    // Remove the first element and return it, if any
+    pub fn pop_front(&mut self) -> Option<T> {
+        match mem::replace(self, Nil) {
+            Cons(head, mut tail) => {
+                *self = mem::replace(&mut *tail, Nil);
+                Some(head)
+            }
+            Nil => None,
+        }
+    }
+
+    // Move all elements of `other` to the end of the list
+    // NOTE: Algorithm complexity: O(n), the whole list is walked to find its end
+    pub fn append(&mut self, other: List<T>) {
+        *self.last_mut() = other;
+    }
+
+    // Reverse the list in place, reusing the existing nodes
+    pub fn reverse(&mut self) {
+        let mut rest = mem::replace(self, Nil);
+
+        while let Cons(_, tail) = &mut rest {
+            // Point the current node at the already reversed part, then make it
+            // the new front of the reversed part
+            let next = mem::replace(&mut **tail, mem::replace(self, Nil));
+            *self = mem::replace(&mut rest, next);
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { next: Some(self) }
+    }
+
+    // Return the trailing `Nil` of the list
+    fn last_mut(&mut self) -> &mut List<T> {
+        let mut cursor = self;
+
+        while let Cons(_, tail) = cursor {
+            cursor = &mut **tail;
+        }
+
+        cursor
+    }
+}
+
+// Pointer to the next node
+// NOTE: a newtype around `Box` only to own the custom `Drop` below, `List`
+//       itself can't implement `Drop` because `pop_front` and `into_iter`
+//       move elements out of `Cons`
+pub struct Link<T>(Box<List<T>>);
+
+impl<T> Link<T> {
+    pub fn new(list: List<T>) -> Link<T> {
+        Link(Box::new(list))
+    }
+}
+
+impl<T> Deref for Link<T> {
+    type Target = List<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Link<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// The compiler-generated drop recurses once per node and overflows the stack
+// on long lists, so unlink the nodes one by one instead
+impl<T> Drop for Link<T> {
+    fn drop(&mut self) {
+        let mut rest = mem::replace(&mut *self.0, Nil);
+
+        while let Cons(_, tail) = &mut rest {
+            // The old `rest` is dropped here, its tail is already `Nil`
+            rest = mem::replace(&mut *tail.0, Nil);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: &'a List<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next {
+            Cons(head, tail) => {
+                self.next = tail;
+                Some(head)
+            }
+            Nil => None,
+        }
+    }
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut List<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next.take()? {
+            Cons(head, tail) => {
+                self.next = Some(tail);
+                Some(head)
+            }
+            Nil => None,
+        }
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Elements are appended, so the list keeps the order of the iterator
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut cursor = self.last_mut();
+
+        for elem in iter {
+            *cursor = Cons(elem, Link::new(Nil));
+
+            cursor = match cursor {
+                Cons(_, tail) => &mut **tail,
+                Nil => unreachable!(),
+            };
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Representation of the list, e.g. `3, 2, 1, Nil`, as `stringify`
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for elem in self.iter() {
+            write!(f, "{}, ", elem)?;
+        }
+
+        write!(f, "Nil")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG_LEN: usize = 10_000_000;
+
+    #[test]
+    fn test_prepend_and_stringify() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.stringify(), "3, 2, 1, Nil");
+        assert_eq!(List::<u32>::new().stringify(), "Nil");
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut list = List::new().prepend(3).prepend(2).prepend(1);
+
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        for elem in &mut list {
+            *elem += 1;
+        }
+        assert_eq!((&list).into_iter().sum::<i32>(), 63);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![11, 21, 31]);
+    }
+
+    #[test]
+    fn test_from_iterator_and_extend() {
+        let mut list: List<String> = vec!["a", "b"].into_iter().map(String::from).collect();
+        list.extend(vec!["c".to_owned(), "d".to_owned()]);
+
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.to_string(), "a, b, c, d, Nil");
+
+        // Extending an empty list, and with nothing
+        let mut empty = List::new();
+        empty.extend(1..=2);
+        empty.extend(None);
+        assert_eq!(empty, (1..=2).collect());
+        assert_eq!(format!("{:?}", empty), "[1, 2]");
+    }
+
+    #[test]
+    fn test_append_peek_and_pop() {
+        let mut list: List<_> = (1..=2).collect();
+        list.append((3..=4).collect());
+
+        assert_eq!(list.peek(), Some(&1));
+        if let Some(head) = list.peek_mut() {
+            *head = 0;
+        }
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.stringify(), "2, 3, 4, Nil");
+
+        list.reverse();
+        assert_eq!(list.stringify(), "4, 3, 2, Nil");
+        assert!(!list.is_empty());
+        assert!(List::<u8>::default().is_empty());
+    }
+
+    #[test]
+    fn test_long_list() {
+        let mut list = List::new();
+        for i in 0..LONG_LEN {
+            list = list.prepend(i);
+        }
+
+        assert_eq!(list.len(), LONG_LEN);
+        assert_eq!(list.peek(), Some(&(LONG_LEN - 1)));
+
+        // Dropping the list must not overflow the stack
+        drop(list);
+    }
+
+    #[test]
+    fn test_long_list_display() {
+        let list: List<u8> = vec![0; 1_000_000].into_iter().collect();
+
+        assert!(list.to_string().ends_with("0, 0, Nil"));
+    }
+}
@@ -9,7 +9,41 @@
 //         3.2.3. Testcase: linked-list
 //    3.3. constants
 
+#[macro_use]
+mod reflect;
+
+mod config;
+mod geometry;
+mod linked_list;
+mod persistent_list;
+mod vm;
+
+// NOTE: in `lib.rs`, shared with `src/bin`
+use _3_custom_types::web_event;
+
 fn main() {
+    // NOTE: the 3.3 configuration is loaded first, so `--print-config` and
+    //       invalid flags or config files stop before the chapter runs
+    {
+        use config::{Args, Config};
+
+        match Args::parse(std::env::args().skip(1))
+            .and_then(|args| Ok((Config::load(&args, std::env::vars_os())?, args.print_config)))
+        {
+            Ok((config, true)) => {
+                println!("{}", config);
+                return;
+            }
+            Ok((config, false)) => {
+                config::init(config);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
     // https://doc.rust-lang.org/rust-by-example/custom_types.html
     println!();
     println!("--- 3. Custom types ---");
@@ -32,20 +66,8 @@ fn main() {
         struct Pair(i32, f32);
 
         // A struct with two fields
-        #[derive(Debug)]
-        struct Point {
-            x: f32,
-            y: f32,
-        }
-
-        // Structs can be reused as fields of another struct
-        #[derive(Debug)]
-        struct Rectangle {
-            // A rectangle can be specified by where the top left and bottom right
-            // corners are in space.
-            top_left: Point,
-            bottom_right: Point,
-        }
+        // NOTE: `Point` and `Rectangle` moved to `geometry.rs`
+        use geometry::{Point, Rectangle};
 
         // Create struct with field init shorthand
         let name = String::from("Peter");
@@ -107,10 +129,11 @@ fn main() {
         //            and a width and height corresponding to the f32.
 
         fn rect_area(rect: Rectangle) -> f32 {
+            // NOTE: normalized, so the corners may come in any order
             let Rectangle {
                 top_left: Point { x: x0, y: y0 },
                 bottom_right: Point { x: x1, y: y1 },
-            } = rect;
+            } = rect.normalized();
 
             (x1 - x0) * (y1 - y0)
         }
@@ -125,37 +148,50 @@ fn main() {
         fn square(point: Point, side: f32) -> Rectangle {
             let Point { x, y } = point;
 
-            Rectangle {
-                top_left: point,
-                bottom_right: Point {
+            // NOTE: a negative `side` is normalized as well
+            Rectangle::new(
+                point,
+                Point {
                     x: x + side,
                     y: y + side,
                 },
-            }
+            )
         }
 
         let point = Point { x: 1f32, y: 2f32 };
         println!("{:?}", square(point, 3f32));
+        println!("{:?}", square(point, -3f32));
+
+        println!();
+        println!("--- Geometry ---");
+
+        let a = Rectangle::new(Point { x: 4.0, y: 4.0 }, Point { x: 0.0, y: 0.0 });
+        let b = Rectangle::square(Point { x: 2.0, y: 3.0 }, 3.0);
+        println!(
+            "a is {}x{} with center {:?}, area {}",
+            a.width(),
+            a.height(),
+            a.center(),
+            rect_area(a)
+        );
+        println!("a contains {:?}: {}", point, a.contains(point));
+        println!("b has area {}", b.area());
+        println!("a intersects b: {}", a.intersects(&b));
+        println!("intersection: {:?}", a.intersection(&b));
+        println!("union: {:?}", a.union(&b));
+        println!("a moved far away: {:?}", a.translate(10.0, -10.0));
+        println!(
+            "intersection with it: {:?}",
+            a.intersection(&a.translate(10.0, -10.0))
+        );
     }
 
     // https://doc.rust-lang.org/rust-by-example/custom_types/enum.html
     println!();
     println!("--- 3.2. Enums ---");
     {
-        // Create an `enum` to classify a web event. Note how both
-        // names and type information together specify the variant:
-        // `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
-        // Each is different and independent.
-        enum WebEvent {
-            // An `enum` may either be `unit-like`,
-            PageLoad,
-            PageUnload,
-            // like tuple structs,
-            KeyPress(char),
-            Paste(String),
-            // or c-like structures.
-            Click { x: i64, y: i64 },
-        }
+        // NOTE: `WebEvent` moved to `web_event.rs`
+        use web_event::{Decoder, Dispatcher, WebEvent};
 
         // A function which takes a `WebEvent` enum as an argument and
         // returns nothing.
@@ -186,29 +222,105 @@ fn main() {
         inspect(load);
         inspect(unload);
 
-        enum VeryVerboseEnumOfThingsToDoWithNumbers {
-            Add,
-            #[allow(dead_code)]
-            Subtract,
+        // NOTE: handlers registered per event kind, fed by recorded terminal
+        //       input: a key press, a focus in, a SGR mouse click, a bracketed
+        //       paste, a X10 mouse click and a focus out
+        let recorded: &[u8] =
+            b"x\x1b[I\x1b[<0;20;80M\x1b[<0;20;80m\x1b[200~my text\x1b[201~\x1b[M !!\x1b[O";
+
+        let mut clicks = 0;
+        {
+            let mut dispatcher = Dispatcher::new();
+            dispatcher
+                .on_page_load(|| println!("handler: page loaded"))
+                .on_page_unload(|| println!("handler: page unloaded"))
+                .on_key_press(|c| println!("handler: pressed '{}'", c))
+                .on_paste(|s| println!("handler: pasted \"{}\"", s))
+                .on_click(|x, y| println!("handler: clicked at x={}, y={}", x, y))
+                .on_click(|_, _| clicks += 1);
+
+            // NOTE: any `io::BufRead` works, e.g. a `BufReader` of a file or
+            //       `io::stdin().lock()`, as the `web_events` binary reads
+            for event in Decoder::new(recorded) {
+                match event {
+                    Ok(event) => {
+                        dispatcher.dispatch(&event);
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
         }
+        println!("clicked {} times", clicks);
 
-        // Creates a type alias
-        type Operations = VeryVerboseEnumOfThingsToDoWithNumbers;
+        match web_event::decode(b"\x1b[200~unterminated") {
+            Ok(events) => println!("{:?}", events),
+            Err(e) => println!("Error: {}", e),
+        }
+
+        // NOTE: `VeryVerboseEnumOfThingsToDoWithNumbers` moved to `vm.rs` and
+        //       grown into the instruction set of a small virtual machine
+        use vm::{Operations, Vm};
 
         // We can refer to each variant via its alias, not its long and inconvenient
         // name.
         let x = Operations::Add;
 
-        impl VeryVerboseEnumOfThingsToDoWithNumbers {
-            fn run(&self, x: i32, y: i32) -> i32 {
-                match self {
-                    Self::Add => x + y,
-                    Self::Subtract => x - y,
-                }
+        println!("{:?}", x.run(1, 2));
+
+        // Count down from 3 to 0
+        let source = "
+            push 3
+            dup     ; 1: loop
+            jz 6
+            push 1
+            sub
+            jmp 1
+        ";
+
+        match vm::assemble(source) {
+            Ok(program) => {
+                println!("disassembled:\n{}", vm::disassemble(&program));
+                println!("result: {:?}", Vm::new().run(&program));
             }
+            Err(e) => println!("Error: {}", e),
         }
 
-        println!("{}", x.run(1, 2));
+        let program = vec![
+            Operations::Push(2),
+            Operations::Push(6),
+            Operations::Push(7),
+            Operations::Multiply,
+            Operations::Swap,
+            Operations::Divide,
+            Operations::Dup,
+            Operations::Pop,
+        ];
+        let mut machine = Vm::default();
+        println!("result: {:?}", machine.run(&program));
+        println!("stack: {:?}", machine.stack());
+
+        for program in &[
+            vec![
+                Operations::Push(i32::MAX),
+                Operations::Push(1),
+                Operations::Add,
+            ],
+            vec![Operations::Push(1), Operations::Push(0), Operations::Divide],
+            vec![Operations::Pop],
+            vec![Operations::Jump(2)],
+            vec![Operations::Jump(0)],
+        ] {
+            // NOTE: `jmp 0` would loop forever without a step limit
+            if let Err(e) = Vm::new().with_max_steps(1_000).run(program) {
+                println!("Error: {}", e);
+            }
+        }
+        for source in &["push x", "mul 2", "jz", "halt", "push 1 2"] {
+            if let Err(e) = vm::assemble(source) {
+                println!("Error: {}", e);
+            }
+        }
+        println!("JumpIfZero: {}", Operations::JumpIfZero(0));
     }
 
     // https://doc.rust-lang.org/rust-by-example/custom_types/enum.html
@@ -255,20 +367,32 @@ fn main() {
     println!();
     println!("--- 3.2.2. C-like ---");
     {
-        // enum with implicit discriminator (starts at 0)
-        #[allow(dead_code)]
-        enum Number {
-            Zero,
-            One,
-            Two,
+        // NOTE: `reflect_enum!` (see `reflect.rs`) adds conversions from the
+        //       discriminant and the variant name
+        use std::convert::TryFrom;
+        use std::str::FromStr;
+
+        reflect_enum! {
+            // enum with implicit discriminator (starts at 0)
+            #[allow(dead_code)]
+            #[derive(Debug)]
+            enum Number {
+                Zero,
+                One,
+                Two,
+            }
         }
 
-        // enum with explicit discriminator
-        #[allow(dead_code)]
-        enum Color {
-            Red = 0xff0000,
-            Green = 0x00ff00,
-            Blue = 0x0000ff,
+        reflect_enum! {
+            #[hex]
+            // enum with explicit discriminator
+            #[allow(dead_code)]
+            #[derive(Debug)]
+            enum Color {
+                Red = 0xff0000,
+                Green = 0x00ff00,
+                Blue = 0x0000ff,
+            }
         }
 
         // `enums` can be cast as integers.
@@ -277,88 +401,35 @@ fn main() {
 
         println!("roses are #{:06x}", Color::Red as i32);
         println!("violets are #{:06x}", Color::Blue as i32);
+
+        // NOTE: and back
+        println!("{} numbers: {:?}", Number::count(), Number::VARIANTS);
+        println!("2 is {:?}", Number::try_from(2));
+        println!("3 is {:?}", Number::try_from(3).map_err(|e| e.to_string()));
+        println!(
+            "\"One\" is {}",
+            "One".parse::<Number>().map_or(-1, |n| n.discriminant())
+        );
+        println!("\"Three\" is {:?}", "Three".parse::<Number>());
+
+        for color in Color::VARIANTS {
+            println!("{} is {}", color, color.hex());
+        }
+        println!("0xff0000 is {:?}", "0xff0000".parse::<Color>());
+        println!("0x00FF00 is {:?}", Color::from_hex("0x00FF00"));
+        println!(
+            "{} colors, Blue is {:?}",
+            Color::count(),
+            Color::from_str("Blue")
+        );
     }
 
     // https://doc.rust-lang.org/rust-by-example/custom_types/enum/testcase_linked_list.html
     println!();
     println!("--- 3.2.3. Testcase: linked-list ---");
     {
-        use List::*;
-
-        enum List {
-            // Cons: Tuple struct that wraps an element and a pointer to the next node
-            Cons(u32, Box<List>),
-            // Nil: A node that signifies the end of the linked list
-            Nil,
-        }
-
-        // Methods can be attached to an enum
-        impl List {
-            // Create an empty list
-            fn new() -> List {
-                // `Nil` has type `List`
-                Nil
-            }
-
-            // Consume a list, and return the same list with a new element at its front
-            fn prepend(self, elem: u32) -> List {
-                // `Cons` also has type List
-                Cons(elem, Box::new(self))
-            }
-
-            // Return the length of the list
-            fn len(&self) -> u32 {
-                // `self` has to be matched, because the behavior of this method
-                // depends on the variant of `self`
-                // `self` has type `&List`, and `*self` has type `List`, matching on a
-                // concrete type `T` is preferred over a match on a reference `&T`
-                // after Rust 2018 you can use self here and tail (with no ref) below as well,
-                // rust will infer &s and ref tail.
-                // See https://doc.rust-lang.org/edition-guide/rust-2018/ownership-and-lifetimes/default-match-bindings.html
-                // match *self {
-                //     // Can't take ownership of the tail, because `self` is borrowed;
-                //     // instead take a reference to the tail
-                //     Cons(_, ref tail) => 1 + tail.len(),
-                //     // Base Case: An empty list has zero length
-                //     Nil => 0,
-                // }
-
-                // NOTE: Rust 2018 variant (*self -> self, ref tail -> tail):
-                //       Algorithm complexity: O(n)
-                match self {
-                    Cons(_, tail) => 1 + tail.len(),
-                    Nil => 0,
-                }
-            }
-
-            // Return representation of the list as a (heap allocated) string
-            fn stringify(&self) -> String {
-                // match *self {
-                //     Cons(head, ref tail) => {
-                //         // `format!` is similar to `print!`, but returns a heap
-                //         // allocated string instead of printing to the console
-                //         format!("{}, {}", head, tail.stringify())
-                //     }
-                //     Nil => {
-                //         format!("Nil")
-                //     }
-                // }
-
-                // NOTE: Rust 2018 variant (*self -> self, ref tail -> tail):
-                match self {
-                    Cons(head, tail) => {
-                        // `format!` is similar to `print!`, but returns a heap
-                        // allocated string instead of printing to the console
-
-                        // NOTE: recursive call
-                        format!("{}, {}", head, tail.stringify())
-                    }
-                    Nil => {
-                        format!("Nil")
-                    }
-                }
-            }
-        }
+        // NOTE: `List` moved to `linked_list.rs`
+        use linked_list::List;
 
         // Create an empty linked list
         let mut list = List::new();
@@ -371,6 +442,64 @@ fn main() {
         // Show the final state of the list
         println!("linked list has length: {}", list.len());
         println!("{}", list.stringify());
+
+        // NOTE: the list is generic, so it holds any element type
+        #[derive(Debug, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        let mut words: List<String> = vec!["hello", "linked"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        words.extend(vec!["list".to_owned()]);
+        words.append(List::new().prepend("!".to_owned()));
+        println!("{} ({} words)", words, words.len());
+
+        if let Some(first) = words.peek_mut() {
+            first.make_ascii_uppercase();
+        }
+        for word in words.iter_mut() {
+            word.push('_');
+        }
+        println!("first word is {:?}", words.peek());
+
+        words.reverse();
+        println!("reversed: {}", words);
+        println!("popped: {:?}", words.pop_front());
+
+        let points: List<Point> = (0..3).map(|i| Point { x: i, y: i * i }).collect();
+        println!("points: {:?}", points);
+        println!(
+            "points are equal to themselves: {}",
+            points == points.iter().map(|p| Point { x: p.x, y: p.y }).collect()
+        );
+
+        let total: i32 = points.into_iter().map(|Point { x, y }| x + y).sum();
+        println!("sum of all coordinates: {}", total);
+
+        let empty: List<Point> = List::default();
+        println!("empty list: {:?} (is empty: {})", empty, empty.is_empty());
+
+        // NOTE: persistent variant, `prepend` shares the tail through `Rc`
+        use persistent_list::PersistentList;
+
+        let base = PersistentList::new().prepend(1).prepend(2);
+        let left = base.prepend(3);
+        let right = base.prepend(4);
+        println!("left: {}, right: {}", left, right);
+        println!(
+            "shared tail: {} (head {:?}, length {})",
+            left.tail(),
+            right.tail().head(),
+            base.clone().len()
+        );
+        println!(
+            "empty persistent list is empty: {}",
+            PersistentList::<u32>::default().is_empty()
+        );
     }
 
     // https://doc.rust-lang.org/rust-by-example/custom_types/constants.html
@@ -378,19 +507,25 @@ fn main() {
     println!("--- 3.3. constants ---");
     {
         // Globals are declared outside all other scopes.
-        static LANGUAGE: &str = "Rust";
-        const THRESHOLD: i32 = 10;
+        // NOTE: `LANGUAGE` and `THRESHOLD` moved to `config.rs`, they are the
+        //       defaults of a runtime configuration now
+        use config::{LANGUAGE, THRESHOLD};
+
+        let config = config::effective();
 
         fn is_big(n: i32) -> bool {
-            // Access constant in some function
-            n > THRESHOLD
+            // Access the effective threshold in some function
+            n > config::effective().threshold.value
         }
 
         let n = 16;
 
         // Access constant in the main thread
-        println!("This is {}", LANGUAGE);
-        println!("The threshold is {}", THRESHOLD);
+        println!("This is {} (default {})", config.language.value, LANGUAGE);
+        println!(
+            "The threshold is {} (default {})",
+            config.threshold.value, THRESHOLD
+        );
         println!("{} is {}", n, if is_big(n) { "big" } else { "small" });
 
         // Error! Cannot modify a `const`.
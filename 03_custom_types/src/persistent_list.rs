@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: an immutable counterpart of `linked_list::List`, nodes are shared
+//       between lists through `Rc` (see 19.8. Rc), so `prepend` and `clone`
+//       are O(1) and never copy the tail
+
+use std::fmt;
+use std::rc::Rc;
+
+pub struct PersistentList<T> {
+    head: Option<Rc<Node<T>>>,
+}
+
+struct Node<T> {
+    elem: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+impl<T> PersistentList<T> {
+    // Create an empty list
+    pub fn new() -> PersistentList<T> {
+        PersistentList { head: None }
+    }
+
+    // Return a new list with `elem` in front, the old list stays untouched and
+    // becomes the tail of the new one
+    pub fn prepend(&self, elem: T) -> PersistentList<T> {
+        PersistentList {
+            head: Some(Rc::new(Node {
+                elem,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    // Return the list without its first element, sharing all of its nodes
+    pub fn tail(&self) -> PersistentList<T> {
+        PersistentList {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    // NOTE: Algorithm complexity: O(n)
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        PersistentList::new()
+    }
+}
+
+// NOTE: O(1), only the reference count of the first node is incremented
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+// Release nodes one by one while this list is their only owner, the first
+// node still shared with another list stops the loop
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Same format as `linked_list::List`, e.g. `3, 2, 1, Nil`
+impl<T: fmt::Display> fmt::Display for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for elem in self.iter() {
+            write!(f, "{}, ", elem)?;
+        }
+
+        write!(f, "Nil")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_tail() {
+        let base = PersistentList::new().prepend(1).prepend(2);
+
+        // `base` itself and the two lists below point at the node of `2`
+        let a = base.prepend(3);
+        let b = base.prepend(4);
+        let node = base.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(node), 3);
+        assert!(Rc::ptr_eq(
+            a.tail().head.as_ref().unwrap(),
+            b.tail().head.as_ref().unwrap()
+        ));
+
+        assert_eq!(a.to_string(), "3, 2, 1, Nil");
+        assert_eq!(b.to_string(), "4, 2, 1, Nil");
+        assert_eq!(base.len(), 2);
+
+        drop(a);
+        drop(b);
+        assert_eq!(Rc::strong_count(base.head.as_ref().unwrap()), 1);
+    }
+
+    #[test]
+    fn test_clone_shares_nodes() {
+        let list = PersistentList::new().prepend("a");
+        let clone = list.clone();
+
+        assert_eq!(Rc::strong_count(list.head.as_ref().unwrap()), 2);
+        assert_eq!(clone.head(), Some(&"a"));
+        assert!(clone.tail().is_empty());
+    }
+
+    #[test]
+    fn test_long_list_drop() {
+        let mut list = PersistentList::new();
+        for i in 0..1_000_000 {
+            list = list.prepend(i);
+        }
+
+        let tail = list.tail();
+        drop(list);
+        assert_eq!(tail.len(), 999_999);
+    }
+}
@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: `reflect_enum!` declares a C-like enum and generates the conversions
+//       that `as i32` alone doesn't give:
+//         - `TryFrom<i32>` back from the discriminant
+//         - `FromStr` and `Display` by the variant name
+//         - `VARIANTS`, `count()`, `name()` and `discriminant()`
+//       With `#[hex]` among the attributes of the enum the discriminant is
+//       also looked up from its hex notation, so `"0xff0000".parse()` gives
+//       `Color::Red`
+//       Variants may have attributes too, but not `#[cfg]`, the generated
+//       code refers to every variant
+
+use std::error;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownDiscriminant {
+    pub enum_name: &'static str,
+    pub value: i32,
+}
+
+impl fmt::Display for UnknownDiscriminant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no `{}` variant with value {}",
+            self.enum_name, self.value
+        )
+    }
+}
+
+impl error::Error for UnknownDiscriminant {}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownVariant {
+    pub enum_name: &'static str,
+    pub name: String,
+}
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no `{}` variant named `{}`", self.enum_name, self.name)
+    }
+}
+
+impl error::Error for UnknownVariant {}
+
+macro_rules! reflect_enum {
+    (@methods hex $name:ident) => {
+        impl $name {
+            // Look the variant up by its discriminant written as `0x...`
+            pub fn from_hex(s: &str) -> Option<$name> {
+                let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+                // `from_str_radix` also takes a sign, e.g. `0x+ff`
+                if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return None;
+                }
+                let value = i32::from_str_radix(digits, 16).ok()?;
+
+                <$name as ::std::convert::TryFrom<i32>>::try_from(value).ok()
+            }
+
+            pub fn hex(&self) -> String {
+                format!("0x{:06x}", self.discriminant())
+            }
+        }
+    };
+    (@parse hex $s:ident) => {
+        if let Some(variant) = Self::from_hex($s) {
+            return Ok(variant);
+        }
+    };
+    (@enum [$($hex:ident)?]
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident $(= $value:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant $(= $value)?),*
+        }
+
+        impl $name {
+            pub const VARIANTS: &'static [$name] = &[$($name::$variant),*];
+
+            pub const fn count() -> usize {
+                Self::VARIANTS.len()
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant)),*
+                }
+            }
+
+            pub fn discriminant(&self) -> i32 {
+                match self {
+                    $($name::$variant => $name::$variant as i32),*
+                }
+            }
+        }
+
+        $(reflect_enum!(@methods $hex $name);)?
+
+        impl ::std::convert::TryFrom<i32> for $name {
+            type Error = $crate::reflect::UnknownDiscriminant;
+
+            fn try_from(value: i32) -> Result<Self, Self::Error> {
+                $(if value == $name::$variant as i32 {
+                    return Ok($name::$variant);
+                })*
+
+                Err($crate::reflect::UnknownDiscriminant {
+                    enum_name: stringify!($name),
+                    value,
+                })
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::reflect::UnknownVariant;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(if s == stringify!($variant) {
+                    return Ok($name::$variant);
+                })*
+
+                $(reflect_enum!(@parse $hex s);)?
+
+                Err($crate::reflect::UnknownVariant {
+                    enum_name: stringify!($name),
+                    name: s.to_owned(),
+                })
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+    };
+    // Take `#[hex]` out of the attributes of the enum, wherever it is
+    (@attrs [$($hex:ident)?] [$($meta:tt)*] #[hex] $($rest:tt)*) => {
+        reflect_enum!(@attrs [hex] [$($meta)*] $($rest)*);
+    };
+    (@attrs [$($hex:ident)?] [$($meta:tt)*] #[$attr:meta] $($rest:tt)*) => {
+        reflect_enum!(@attrs [$($hex)?] [$($meta)* #[$attr]] $($rest)*);
+    };
+    (@attrs [$($hex:ident)?] [$($meta:tt)*] $($rest:tt)*) => {
+        reflect_enum!(@enum [$($hex)?] $($meta)* $($rest)*);
+    };
+    ($($rest:tt)*) => {
+        reflect_enum!(@attrs [] [] $($rest)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    // As in 3.2.2
+    reflect_enum! {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Number {
+            Zero,
+            One,
+            Two,
+        }
+    }
+
+    reflect_enum! {
+        #[hex]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Color {
+            Red = 0xff0000,
+            Green = 0x00ff00,
+            Blue = 0x0000ff,
+        }
+    }
+
+    // `#[hex]` after other attributes, attributes on variants
+    reflect_enum! {
+        #[derive(Debug, PartialEq)]
+        #[hex]
+        #[allow(dead_code)]
+        enum Level {
+            /// The lowest
+            Low = 0x10,
+            #[allow(clippy::all)]
+            High = 0x20,
+        }
+    }
+
+    #[test]
+    fn test_variants() {
+        assert_eq!(Number::VARIANTS, &[Number::Zero, Number::One, Number::Two]);
+        assert_eq!(Number::count(), 3);
+        assert_eq!(Color::VARIANTS, &[Color::Red, Color::Green, Color::Blue]);
+        assert_eq!(Color::count(), 3);
+        assert_eq!(Level::count(), 2);
+    }
+
+    #[test]
+    fn test_names() {
+        let names: Vec<_> = Number::VARIANTS.iter().map(Number::name).collect();
+        assert_eq!(names, ["Zero", "One", "Two"]);
+        assert_eq!(Color::Green.to_string(), "Green");
+
+        assert_eq!("Two".parse(), Ok(Number::Two));
+        assert_eq!("Blue".parse(), Ok(Color::Blue));
+        assert_eq!(
+            "two".parse::<Number>().unwrap_err().to_string(),
+            "no `Number` variant named `two`"
+        );
+    }
+
+    #[test]
+    fn test_discriminants() {
+        let values: Vec<_> = Number::VARIANTS.iter().map(Number::discriminant).collect();
+        assert_eq!(values, [0, 1, 2]);
+        assert_eq!(Color::Red.discriminant(), 0xff0000);
+
+        assert_eq!(Number::try_from(1), Ok(Number::One));
+        assert_eq!(Color::try_from(0x00ff00), Ok(Color::Green));
+        assert_eq!(
+            Number::try_from(3).unwrap_err().to_string(),
+            "no `Number` variant with value 3"
+        );
+        assert!(Color::try_from(0).is_err());
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(Color::Red.hex(), "0xff0000");
+        assert_eq!(Color::Blue.hex(), "0x0000ff");
+        assert_eq!(Level::Low.hex(), "0x000010");
+
+        assert_eq!(Color::from_hex("0x00ff00"), Some(Color::Green));
+        assert_eq!(Color::from_hex("0XFF0000"), Some(Color::Red));
+        assert_eq!(Color::from_hex("ff0000"), None);
+        assert_eq!(Color::from_hex("0x123456"), None);
+        assert_eq!(Color::from_hex("0x+ff0000"), None);
+        assert_eq!(Color::from_hex("0x"), None);
+        assert_eq!("0x0000ff".parse(), Ok(Color::Blue));
+        assert_eq!("0x20".parse(), Ok(Level::High));
+
+        // Only with `#[hex]`
+        assert!("0x1".parse::<Number>().is_err());
+    }
+}
@@ -0,0 +1,500 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 3.2 `VeryVerboseEnumOfThingsToDoWithNumbers` grown into the
+//       instruction set of a small stack-based virtual machine
+
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VeryVerboseEnumOfThingsToDoWithNumbers {
+    // Push the value on top of the stack
+    Push(i32),
+    // Discard the top of the stack
+    Pop,
+    // Pop `y` and `x`, push `x <op> y`
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    // Push a copy of the top of the stack
+    Dup,
+    // Exchange the two topmost values
+    Swap,
+    // Continue at the given instruction index
+    Jump(usize),
+    // Pop the top of the stack and jump if it is zero
+    JumpIfZero(usize),
+}
+
+// Creates a type alias
+pub type Operations = VeryVerboseEnumOfThingsToDoWithNumbers;
+
+impl VeryVerboseEnumOfThingsToDoWithNumbers {
+    // Apply an arithmetic operation, `None` on overflow, division by zero or
+    // if the operation is not arithmetic at all
+    pub fn run(&self, x: i32, y: i32) -> Option<i32> {
+        match self {
+            Self::Add => x.checked_add(y),
+            Self::Subtract => x.checked_sub(y),
+            Self::Multiply => x.checked_mul(y),
+            Self::Divide => x.checked_div(y),
+            _ => None,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Push(_) => "push",
+            Self::Pop => "pop",
+            Self::Add => "add",
+            Self::Subtract => "sub",
+            Self::Multiply => "mul",
+            Self::Divide => "div",
+            Self::Dup => "dup",
+            Self::Swap => "swap",
+            Self::Jump(_) => "jmp",
+            Self::JumpIfZero(_) => "jz",
+        }
+    }
+}
+
+// Disassemble a single instruction, e.g. `push 3` or `jz 7`
+impl fmt::Display for VeryVerboseEnumOfThingsToDoWithNumbers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Push(value) => write!(f, "{} {}", self.mnemonic(), value),
+            Self::Jump(target) | Self::JumpIfZero(target) => {
+                write!(f, "{} {}", self.mnemonic(), target)
+            }
+            _ => write!(f, "{}", self.mnemonic()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VmError {
+    StackUnderflow { pc: usize },
+    Overflow { pc: usize },
+    DivisionByZero { pc: usize },
+    InvalidJump { pc: usize, target: usize },
+    // The program ran `max_steps` instructions without halting, e.g. `jmp 0`
+    StepLimit { pc: usize, max_steps: usize },
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VmError::StackUnderflow { pc } => write!(f, "stack underflow at {}", pc),
+            VmError::Overflow { pc } => write!(f, "arithmetic overflow at {}", pc),
+            VmError::DivisionByZero { pc } => write!(f, "division by zero at {}", pc),
+            VmError::InvalidJump { pc, target } => {
+                write!(f, "jump to {} out of the program at {}", target, pc)
+            }
+            VmError::StepLimit { pc, max_steps } => {
+                write!(f, "no halt after {} steps, at {}", max_steps, pc)
+            }
+        }
+    }
+}
+
+impl error::Error for VmError {}
+
+#[derive(Debug)]
+pub struct Vm {
+    stack: Vec<i32>,
+    // Instructions a single `run` may execute
+    max_steps: usize,
+}
+
+impl Vm {
+    pub const DEFAULT_MAX_STEPS: usize = 1_000_000;
+
+    pub fn new() -> Vm {
+        Vm {
+            stack: Vec::new(),
+            max_steps: Vm::DEFAULT_MAX_STEPS,
+        }
+    }
+
+    pub fn with_max_steps(self, max_steps: usize) -> Vm {
+        Vm { max_steps, ..self }
+    }
+
+    pub fn stack(&self) -> &[i32] {
+        &self.stack
+    }
+
+    // Execute the program until it runs past its last instruction, returns the
+    // final state of the stack
+    // NOTE: the stack is kept between runs, so programs can be chained, on
+    //       error it is restored to what it was before the run
+    pub fn run(&mut self, program: &[Operations]) -> Result<&[i32], VmError> {
+        let saved = self.stack.clone();
+
+        match self.execute(program) {
+            Ok(()) => Ok(&self.stack),
+            Err(e) => {
+                self.stack = saved;
+                Err(e)
+            }
+        }
+    }
+
+    fn execute(&mut self, program: &[Operations]) -> Result<(), VmError> {
+        let mut pc = 0;
+        let mut steps = 0;
+
+        while let Some(op) = program.get(pc) {
+            if steps == self.max_steps {
+                return Err(VmError::StepLimit {
+                    pc,
+                    max_steps: self.max_steps,
+                });
+            }
+            steps += 1;
+
+            let mut next = pc + 1;
+
+            match *op {
+                Operations::Push(value) => self.stack.push(value),
+                Operations::Pop => {
+                    self.pop(pc)?;
+                }
+                Operations::Add
+                | Operations::Subtract
+                | Operations::Multiply
+                | Operations::Divide => {
+                    let y = self.pop(pc)?;
+                    let x = self.pop(pc)?;
+
+                    if *op == Operations::Divide && y == 0 {
+                        return Err(VmError::DivisionByZero { pc });
+                    }
+
+                    self.stack
+                        .push(op.run(x, y).ok_or(VmError::Overflow { pc })?);
+                }
+                Operations::Dup => {
+                    let top = self.pop(pc)?;
+                    self.stack.push(top);
+                    self.stack.push(top);
+                }
+                Operations::Swap => {
+                    let y = self.pop(pc)?;
+                    let x = self.pop(pc)?;
+                    self.stack.push(y);
+                    self.stack.push(x);
+                }
+                Operations::Jump(target) => next = target,
+                Operations::JumpIfZero(target) => {
+                    if self.pop(pc)? == 0 {
+                        next = target;
+                    }
+                }
+            }
+
+            // Jumping right past the last instruction is a valid way to halt
+            if next > program.len() {
+                return Err(VmError::InvalidJump { pc, target: next });
+            }
+
+            pc = next;
+        }
+
+        Ok(())
+    }
+
+    fn pop(&mut self, pc: usize) -> Result<i32, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow { pc })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleErrorKind {
+    UnknownMnemonic(String),
+    MissingOperand,
+    InvalidOperand(String),
+    UnexpectedOperand(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssembleError {
+    // 1-based line of the source
+    pub line: usize,
+    pub kind: AssembleErrorKind,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            AssembleErrorKind::UnknownMnemonic(s) => write!(f, "unknown mnemonic `{}`", s),
+            AssembleErrorKind::MissingOperand => write!(f, "missing operand"),
+            AssembleErrorKind::InvalidOperand(s) => write!(f, "invalid operand `{}`", s),
+            AssembleErrorKind::UnexpectedOperand(s) => write!(f, "unexpected operand `{}`", s),
+        }
+    }
+}
+
+impl error::Error for AssembleError {}
+
+// Parse one instruction per line, e.g. `push 3`; everything after `;` is a
+// comment, blank lines are skipped
+pub fn assemble(source: &str) -> Result<Vec<Operations>, AssembleError> {
+    let mut program = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let code = line.split(';').next().unwrap_or_default();
+        let mut words = code.split_whitespace();
+
+        let mnemonic = match words.next() {
+            Some(mnemonic) => mnemonic,
+            None => continue,
+        };
+        let operand = words.next();
+        let error = |kind| AssembleError {
+            line: index + 1,
+            kind,
+        };
+
+        if let Some(extra) = words.next() {
+            return Err(error(AssembleErrorKind::UnexpectedOperand(
+                extra.to_owned(),
+            )));
+        }
+
+        let op = match (mnemonic, operand) {
+            ("push", Some(value)) => Operations::Push(
+                value
+                    .parse()
+                    .map_err(|_| error(AssembleErrorKind::InvalidOperand(value.to_owned())))?,
+            ),
+            ("jmp", Some(target)) | ("jz", Some(target)) => {
+                let target = target
+                    .parse()
+                    .map_err(|_| error(AssembleErrorKind::InvalidOperand(target.to_owned())))?;
+
+                if mnemonic == "jmp" {
+                    Operations::Jump(target)
+                } else {
+                    Operations::JumpIfZero(target)
+                }
+            }
+            ("push", None) | ("jmp", None) | ("jz", None) => {
+                return Err(error(AssembleErrorKind::MissingOperand))
+            }
+            (_, Some(operand)) if is_mnemonic(mnemonic) => {
+                return Err(error(AssembleErrorKind::UnexpectedOperand(
+                    operand.to_owned(),
+                )))
+            }
+            ("pop", None) => Operations::Pop,
+            ("add", None) => Operations::Add,
+            ("sub", None) => Operations::Subtract,
+            ("mul", None) => Operations::Multiply,
+            ("div", None) => Operations::Divide,
+            ("dup", None) => Operations::Dup,
+            ("swap", None) => Operations::Swap,
+            _ => {
+                return Err(error(AssembleErrorKind::UnknownMnemonic(
+                    mnemonic.to_owned(),
+                )))
+            }
+        };
+
+        program.push(op);
+    }
+
+    Ok(program)
+}
+
+// Print the program back, one instruction per line, so the output can be
+// assembled again
+pub fn disassemble(program: &[Operations]) -> String {
+    program
+        .iter()
+        .map(|op| op.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Vm::new()
+    }
+}
+
+fn is_mnemonic(s: &str) -> bool {
+    matches!(
+        s,
+        "push" | "pop" | "add" | "sub" | "mul" | "div" | "dup" | "swap" | "jmp" | "jz"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use VeryVerboseEnumOfThingsToDoWithNumbers::*;
+
+    fn run(program: &[Operations]) -> Result<Vec<i32>, VmError> {
+        Vm::new().run(program).map(<[i32]>::to_vec)
+    }
+
+    #[test]
+    fn test_opcodes() {
+        assert_eq!(run(&[Push(1), Push(2)]), Ok(vec![1, 2]));
+        assert_eq!(run(&[Push(1), Push(2), Pop]), Ok(vec![1]));
+        assert_eq!(run(&[Push(7), Push(2), Add]), Ok(vec![9]));
+        assert_eq!(run(&[Push(7), Push(2), Subtract]), Ok(vec![5]));
+        assert_eq!(run(&[Push(7), Push(2), Multiply]), Ok(vec![14]));
+        assert_eq!(run(&[Push(7), Push(2), Divide]), Ok(vec![3]));
+        assert_eq!(run(&[Push(-7), Push(2), Divide]), Ok(vec![-3]));
+        assert_eq!(run(&[Push(7), Dup]), Ok(vec![7, 7]));
+        assert_eq!(run(&[Push(1), Push(2), Swap]), Ok(vec![2, 1]));
+        assert_eq!(run(&[Jump(2), Push(1), Push(2)]), Ok(vec![2]));
+        // Jumping right past the end halts
+        assert_eq!(run(&[Jump(2), Push(1)]), Ok(vec![]));
+        assert_eq!(
+            run(&[Push(0), JumpIfZero(3), Push(1), Push(2)]),
+            Ok(vec![2])
+        );
+        assert_eq!(
+            run(&[Push(5), JumpIfZero(3), Push(1), Push(2)]),
+            Ok(vec![1, 2])
+        );
+
+        assert_eq!(Add.run(1, 2), Some(3));
+        assert_eq!(Divide.run(1, 0), None);
+        assert_eq!(Dup.run(1, 2), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(run(&[Pop]), Err(VmError::StackUnderflow { pc: 0 }));
+        assert_eq!(run(&[Push(1), Add]), Err(VmError::StackUnderflow { pc: 1 }));
+        assert_eq!(run(&[Dup]), Err(VmError::StackUnderflow { pc: 0 }));
+        assert_eq!(
+            run(&[Push(1), Swap]),
+            Err(VmError::StackUnderflow { pc: 1 })
+        );
+        assert_eq!(
+            run(&[JumpIfZero(0)]),
+            Err(VmError::StackUnderflow { pc: 0 })
+        );
+        assert_eq!(
+            run(&[Push(i32::MAX), Push(1), Add]),
+            Err(VmError::Overflow { pc: 2 })
+        );
+        assert_eq!(
+            run(&[Push(i32::MIN), Push(-1), Divide]),
+            Err(VmError::Overflow { pc: 2 })
+        );
+        assert_eq!(
+            run(&[Push(1), Push(0), Divide]),
+            Err(VmError::DivisionByZero { pc: 2 })
+        );
+        assert_eq!(
+            run(&[Jump(3), Push(1)]),
+            Err(VmError::InvalidJump { pc: 0, target: 3 })
+        );
+        assert_eq!(
+            run(&[Jump(0)]),
+            Err(VmError::StepLimit {
+                pc: 0,
+                max_steps: Vm::DEFAULT_MAX_STEPS
+            })
+        );
+        assert_eq!(
+            Vm::new()
+                .with_max_steps(2)
+                .run(&[Push(1), Push(2), Push(3)]),
+            Err(VmError::StepLimit {
+                pc: 2,
+                max_steps: 2
+            })
+        );
+        assert_eq!(
+            VmError::StepLimit {
+                pc: 0,
+                max_steps: 5
+            }
+            .to_string(),
+            "no halt after 5 steps, at 0"
+        );
+    }
+
+    #[test]
+    fn test_stack_restored_on_error() {
+        let mut vm = Vm::new();
+        assert_eq!(vm.run(&[Push(1), Push(2)]), Ok(&[1, 2][..]));
+
+        // Pops both values before failing
+        assert!(vm.run(&[Push(0), Divide, Divide]).is_err());
+        assert_eq!(vm.stack(), &[1, 2]);
+
+        assert_eq!(vm.run(&[Add]), Ok(&[3][..]));
+    }
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            push -3   ; a comment
+            dup
+
+            jz 5
+            pop
+            add
+            sub
+            mul
+            div
+            swap
+            jmp 0
+        ";
+        assert_eq!(
+            assemble(source),
+            Ok(vec![
+                Push(-3),
+                Dup,
+                JumpIfZero(5),
+                Pop,
+                Add,
+                Subtract,
+                Multiply,
+                Divide,
+                Swap,
+                Jump(0),
+            ])
+        );
+
+        let error = |source: &str| assemble(source).unwrap_err().to_string();
+        assert_eq!(error("push"), "line 1: missing operand");
+        assert_eq!(error("push x"), "line 1: invalid operand `x`");
+        assert_eq!(error("\njz -1"), "line 2: invalid operand `-1`");
+        assert_eq!(error("mul 2"), "line 1: unexpected operand `2`");
+        assert_eq!(error("push 1 2"), "line 1: unexpected operand `2`");
+        assert_eq!(error("halt"), "line 1: unknown mnemonic `halt`");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program = vec![
+            Push(i32::MIN),
+            Pop,
+            Add,
+            Subtract,
+            Multiply,
+            Divide,
+            Dup,
+            Swap,
+            Jump(3),
+            JumpIfZero(0),
+        ];
+        let source = disassemble(&program);
+
+        assert_eq!(source.lines().next(), Some("push -2147483648"));
+        assert_eq!(assemble(&source), Ok(program));
+        assert_eq!(disassemble(&[]), "");
+    }
+}
@@ -0,0 +1,511 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 3.2 `WebEvent` moved here together with a dispatcher for event
+//       handlers and a decoder of raw terminal input into events
+
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str;
+
+// Create an `enum` to classify a web event. Note how both
+// names and type information together specify the variant:
+// `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
+// Each is different and independent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebEvent {
+    // An `enum` may either be `unit-like`,
+    PageLoad,
+    PageUnload,
+    // like tuple structs,
+    KeyPress(char),
+    Paste(String),
+    // or c-like structures.
+    Click { x: i64, y: i64 },
+}
+
+// Calls every handler registered for the kind of a dispatched event, in the
+// order of registration
+#[derive(Default)]
+pub struct Dispatcher<'a> {
+    page_load: Handlers<dyn FnMut() + 'a>,
+    page_unload: Handlers<dyn FnMut() + 'a>,
+    key_press: Handlers<dyn FnMut(char) + 'a>,
+    paste: Handlers<dyn FnMut(&str) + 'a>,
+    click: Handlers<dyn FnMut(i64, i64) + 'a>,
+}
+
+type Handlers<H> = Vec<Box<H>>;
+
+impl<'a> Dispatcher<'a> {
+    pub fn new() -> Dispatcher<'a> {
+        Dispatcher::default()
+    }
+
+    pub fn on_page_load<F: FnMut() + 'a>(&mut self, handler: F) -> &mut Self {
+        self.page_load.push(Box::new(handler));
+        self
+    }
+
+    pub fn on_page_unload<F: FnMut() + 'a>(&mut self, handler: F) -> &mut Self {
+        self.page_unload.push(Box::new(handler));
+        self
+    }
+
+    pub fn on_key_press<F: FnMut(char) + 'a>(&mut self, handler: F) -> &mut Self {
+        self.key_press.push(Box::new(handler));
+        self
+    }
+
+    pub fn on_paste<F: FnMut(&str) + 'a>(&mut self, handler: F) -> &mut Self {
+        self.paste.push(Box::new(handler));
+        self
+    }
+
+    pub fn on_click<F: FnMut(i64, i64) + 'a>(&mut self, handler: F) -> &mut Self {
+        self.click.push(Box::new(handler));
+        self
+    }
+
+    // Returns the number of handlers called
+    pub fn dispatch(&mut self, event: &WebEvent) -> usize {
+        match event {
+            WebEvent::PageLoad => call_all(&mut self.page_load, |handler| handler()),
+            WebEvent::PageUnload => call_all(&mut self.page_unload, |handler| handler()),
+            WebEvent::KeyPress(c) => call_all(&mut self.key_press, |handler| handler(*c)),
+            WebEvent::Paste(s) => call_all(&mut self.paste, |handler| handler(s)),
+            WebEvent::Click { x, y } => call_all(&mut self.click, |handler| handler(*x, *y)),
+        }
+    }
+}
+
+fn call_all<H: ?Sized, F: FnMut(&mut H)>(handlers: &mut [Box<H>], mut call: F) -> usize {
+    for handler in handlers.iter_mut() {
+        call(handler);
+    }
+
+    handlers.len()
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Io(io::Error),
+    // A key press or a pasted text is not valid UTF-8
+    InvalidUtf8(Vec<u8>),
+    // A mouse report has unexpected parameters
+    InvalidMouse(Vec<u8>),
+    // The input ended inside of a bracketed paste
+    UnterminatedPaste,
+    // The input ended inside of an escape sequence, with its bytes so far
+    UnterminatedSequence(Vec<u8>),
+    // An escape sequence that is not an event, e.g. `ESC [ A` for the up
+    // arrow, with all of its bytes, decoding can go on after it
+    UnknownSequence(Vec<u8>),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Io(..) => write!(f, "failed to read the input"),
+            DecodeError::InvalidUtf8(bytes) => write!(f, "invalid UTF-8 {:02X?}", bytes),
+            DecodeError::InvalidMouse(bytes) => {
+                write!(
+                    f,
+                    "invalid mouse sequence {:?}",
+                    String::from_utf8_lossy(bytes)
+                )
+            }
+            DecodeError::UnterminatedPaste => write!(f, "unterminated bracketed paste"),
+            DecodeError::UnterminatedSequence(bytes) => {
+                write!(f, "unterminated escape sequence {:02X?}", bytes)
+            }
+            DecodeError::UnknownSequence(bytes) => {
+                write!(f, "unknown escape sequence {:02X?}", bytes)
+            }
+        }
+    }
+}
+
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DecodeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> DecodeError {
+        DecodeError::Io(err)
+    }
+}
+
+const ESC: u8 = 0x1b;
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+// Decodes raw terminal input, e.g. a recorded session or `io::stdin().lock()`:
+//   - `ESC [ < b ; x ; y M` (xterm SGR) and `ESC [ M b x y` (xterm X10) mouse
+//     button presses become `Click`, releases, motion and wheel are skipped
+//   - `ESC [ 200 ~ text ESC [ 201 ~` (bracketed paste) becomes `Paste`
+//   - `ESC [ I` and `ESC [ O` (focus in/out) become `PageLoad`/`PageUnload`
+//   - any other character becomes `KeyPress`, also `ESC` when not followed by
+//     `[`, so `ESC O P` (F1) is three key presses
+//   - other control sequences are `DecodeError::UnknownSequence`, the
+//     iterator goes on with the next event after them
+// NOTE: the input is consumed byte by byte, so it has to be buffered, e.g.
+//       files wrapped in `io::BufReader`
+pub struct Decoder<R> {
+    bytes: io::Bytes<R>,
+    // A byte read ahead that doesn't belong to the current event
+    pending: Option<u8>,
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder {
+            bytes: reader.bytes(),
+            pending: None,
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, DecodeError> {
+        match self.pending.take() {
+            Some(byte) => Ok(Some(byte)),
+            None => Ok(self.bytes.next().transpose()?),
+        }
+    }
+
+    fn decode_event(&mut self) -> Result<Option<WebEvent>, DecodeError> {
+        while let Some(byte) = self.next_byte()? {
+            let event = match byte {
+                ESC => self.decode_escape()?,
+                _ => Some(WebEvent::KeyPress(self.decode_char(byte)?)),
+            };
+
+            if event.is_some() {
+                return Ok(event);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn decode_char(&mut self, first: u8) -> Result<char, DecodeError> {
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(DecodeError::InvalidUtf8(vec![first])),
+        };
+
+        let mut bytes = vec![first];
+        while bytes.len() < len {
+            match self.next_byte()? {
+                Some(byte @ 0x80..=0xbf) => bytes.push(byte),
+                // Not a continuation byte, it starts the next event instead
+                byte => {
+                    self.pending = byte;
+                    return Err(DecodeError::InvalidUtf8(bytes));
+                }
+            }
+        }
+
+        match str::from_utf8(&bytes) {
+            Ok(s) => Ok(s.chars().next().unwrap_or_default()),
+            Err(_) => Err(DecodeError::InvalidUtf8(bytes)),
+        }
+    }
+
+    // Decode what follows an `ESC`, `None` for skipped sequences
+    fn decode_escape(&mut self) -> Result<Option<WebEvent>, DecodeError> {
+        match self.next_byte()? {
+            Some(b'[') => {}
+            // A lone `ESC` is the Escape key itself
+            byte => {
+                self.pending = byte;
+                return Ok(Some(WebEvent::KeyPress('\x1b')));
+            }
+        }
+
+        // Control sequence: parameter bytes, then a single final byte
+        let mut sequence = vec![ESC, b'['];
+        let last = loop {
+            match self.next_byte()? {
+                Some(byte) => sequence.push(byte),
+                None => return Err(DecodeError::UnterminatedSequence(sequence)),
+            }
+
+            if let Some(&byte @ 0x40..=0x7e) = sequence.last() {
+                break byte;
+            }
+        };
+
+        match (&sequence[2..sequence.len() - 1], last) {
+            ([], b'I') => Ok(Some(WebEvent::PageLoad)),
+            ([], b'O') => Ok(Some(WebEvent::PageUnload)),
+            ([], b'M') => self.decode_x10_mouse(),
+            ([b'<', sgr @ ..], b'M') => decode_sgr_mouse(sgr),
+            // A SGR button release
+            ([b'<', sgr @ ..], b'm') => decode_sgr_mouse(sgr).map(|_| None),
+            (b"200", b'~') => self.decode_paste().map(Some),
+            _ => Err(DecodeError::UnknownSequence(sequence)),
+        }
+    }
+
+    fn decode_x10_mouse(&mut self) -> Result<Option<WebEvent>, DecodeError> {
+        let mut report = [0; 3];
+
+        for byte in report.iter_mut() {
+            match self.next_byte()? {
+                Some(b) if b >= 32 => *byte = b - 32,
+                Some(b) => return Err(DecodeError::InvalidMouse(vec![b])),
+                None => return Err(DecodeError::InvalidMouse(report.to_vec())),
+            }
+        }
+
+        let [button, x, y] = report;
+
+        // In X10 mode a release is reported as button 3
+        if is_button_press(button.into()) && button & 0b11 != 3 {
+            Ok(Some(WebEvent::Click {
+                x: x.into(),
+                y: y.into(),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn decode_paste(&mut self) -> Result<WebEvent, DecodeError> {
+        let mut text = Vec::new();
+
+        while !text.ends_with(PASTE_END) {
+            match self.next_byte()? {
+                Some(byte) => text.push(byte),
+                None => return Err(DecodeError::UnterminatedPaste),
+            }
+        }
+
+        text.truncate(text.len() - PASTE_END.len());
+
+        String::from_utf8(text)
+            .map(WebEvent::Paste)
+            .map_err(|e| DecodeError::InvalidUtf8(e.into_bytes()))
+    }
+}
+
+// `b;x;y` of a SGR mouse report, `None` for all but button presses
+fn decode_sgr_mouse(params: &[u8]) -> Result<Option<WebEvent>, DecodeError> {
+    let invalid = || DecodeError::InvalidMouse(params.to_vec());
+
+    let values = str::from_utf8(params)
+        .map_err(|_| invalid())?
+        .split(';')
+        .map(str::parse::<i64>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+
+    match values.as_slice() {
+        [button, x, y] if is_button_press(*button) => Ok(Some(WebEvent::Click { x: *x, y: *y })),
+        [_, _, _] => Ok(None),
+        _ => Err(invalid()),
+    }
+}
+
+// Motion (bit 5) and wheel (bit 6) reports are not clicks
+fn is_button_press(button: i64) -> bool {
+    button & (32 | 64) == 0
+}
+
+impl<R: BufRead> Iterator for Decoder<R> {
+    type Item = Result<WebEvent, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode_event().transpose()
+    }
+}
+
+// Decode a whole recorded input at once
+pub fn decode(input: &[u8]) -> Result<Vec<WebEvent>, DecodeError> {
+    Decoder::new(input).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(input: &[u8]) -> Vec<Result<WebEvent, String>> {
+        Decoder::new(input)
+            .map(|event| event.map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keys_and_focus() {
+        assert_eq!(
+            decode("aé€\x1b[I\x1b[O".as_bytes()).unwrap(),
+            vec![
+                WebEvent::KeyPress('a'),
+                WebEvent::KeyPress('é'),
+                WebEvent::KeyPress('€'),
+                WebEvent::PageLoad,
+                WebEvent::PageUnload,
+            ]
+        );
+        // A lone `ESC` is the Escape key
+        assert_eq!(decode(b"\x1b").unwrap(), vec![WebEvent::KeyPress('\x1b')]);
+    }
+
+    #[test]
+    fn test_esc_o() {
+        // SS3 sequences are not control sequences
+        assert_eq!(
+            decode(b"\x1bOP").unwrap(),
+            vec![
+                WebEvent::KeyPress('\x1b'),
+                WebEvent::KeyPress('O'),
+                WebEvent::KeyPress('P'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sgr_mouse() {
+        let click = WebEvent::Click { x: 20, y: 80 };
+
+        // Press, then release
+        assert_eq!(decode(b"\x1b[<0;20;80M").unwrap(), vec![click.clone()]);
+        assert_eq!(decode(b"\x1b[<0;20;80m").unwrap(), vec![]);
+        assert_eq!(
+            decode(b"\x1b[<2;20;80M\x1b[<2;20;80m").unwrap(),
+            vec![click]
+        );
+        // Wheel up and down, and motion
+        assert_eq!(decode(b"\x1b[<64;1;1M\x1b[<65;1;1M").unwrap(), vec![]);
+        assert_eq!(decode(b"\x1b[<32;5;5M").unwrap(), vec![]);
+
+        assert_eq!(
+            decode_all(b"\x1b[<0;;1M\x1b[<0;1m"),
+            vec![
+                Err("invalid mouse sequence \"0;;1\"".to_owned()),
+                Err("invalid mouse sequence \"0;1\"".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_x10_mouse() {
+        // Button, x and y are offset by 32
+        assert_eq!(
+            decode(b"\x1b[M !!").unwrap(),
+            vec![WebEvent::Click { x: 1, y: 1 }]
+        );
+        // Release is button 3, wheel up and down have bit 6 set
+        assert_eq!(decode(b"\x1b[M#!!").unwrap(), vec![]);
+        assert_eq!(decode(b"\x1b[M`!!\x1b[Ma!!").unwrap(), vec![]);
+
+        assert!(matches!(
+            decode(b"\x1b[M\x01!!"),
+            Err(DecodeError::InvalidMouse(bytes)) if bytes == [0x01]
+        ));
+        assert!(matches!(
+            decode(b"\x1b[M !"),
+            Err(DecodeError::InvalidMouse(_))
+        ));
+    }
+
+    #[test]
+    fn test_paste() {
+        assert_eq!(
+            decode(b"\x1b[200~my \x1b[A text\x1b[201~x").unwrap(),
+            vec![
+                WebEvent::Paste("my \x1b[A text".to_owned()),
+                WebEvent::KeyPress('x'),
+            ]
+        );
+        assert_eq!(
+            decode(b"\x1b[200~\x1b[201~").unwrap(),
+            vec![WebEvent::Paste(String::new())]
+        );
+        assert!(matches!(
+            decode(b"\x1b[200~unterminated\x1b[201"),
+            Err(DecodeError::UnterminatedPaste)
+        ));
+        assert!(matches!(
+            decode(b"\x1b[200~\xff\x1b[201~"),
+            Err(DecodeError::InvalidUtf8(bytes)) if bytes == [0xff]
+        ));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        assert_eq!(
+            decode_all(b"\xffa\xc3(\xe2\x82"),
+            vec![
+                Err("invalid UTF-8 [FF]".to_owned()),
+                Ok(WebEvent::KeyPress('a')),
+                Err("invalid UTF-8 [C3]".to_owned()),
+                Ok(WebEvent::KeyPress('(')),
+                Err("invalid UTF-8 [E2, 82]".to_owned()),
+            ]
+        );
+        assert_eq!(
+            decode_all(b"\xc3\x1b[I\xe2\x82a"),
+            vec![
+                Err("invalid UTF-8 [C3]".to_owned()),
+                Ok(WebEvent::PageLoad),
+                Err("invalid UTF-8 [E2, 82]".to_owned()),
+                Ok(WebEvent::KeyPress('a')),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incomplete_and_unknown_sequences() {
+        assert_eq!(
+            decode_all(b"\x1b[1;5"),
+            vec![Err(
+                "unterminated escape sequence [1B, 5B, 31, 3B, 35]".to_owned()
+            )]
+        );
+        assert_eq!(
+            decode_all(b"\x1b["),
+            vec![Err("unterminated escape sequence [1B, 5B]".to_owned())]
+        );
+        // The decoder goes on after an unknown sequence
+        assert_eq!(
+            decode_all(b"\x1b[Aa\x1b[1;5Cb"),
+            vec![
+                Err("unknown escape sequence [1B, 5B, 41]".to_owned()),
+                Ok(WebEvent::KeyPress('a')),
+                Err("unknown escape sequence [1B, 5B, 31, 3B, 35, 43]".to_owned()),
+                Ok(WebEvent::KeyPress('b')),
+            ]
+        );
+        assert!(matches!(
+            decode(b"\x1b[5~"),
+            Err(DecodeError::UnknownSequence(bytes)) if bytes == b"\x1b[5~"
+        ));
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let mut keys = String::new();
+        let mut clicks = 0;
+        {
+            let mut dispatcher = Dispatcher::new();
+            dispatcher
+                .on_key_press(|c| keys.push(c))
+                .on_click(|_, _| clicks += 1);
+
+            assert_eq!(dispatcher.dispatch(&WebEvent::KeyPress('a')), 1);
+            assert_eq!(dispatcher.dispatch(&WebEvent::Click { x: 0, y: 0 }), 1);
+            assert_eq!(dispatcher.dispatch(&WebEvent::PageLoad), 0);
+        }
+
+        assert_eq!(keys, "a");
+        assert_eq!(clicks, 1);
+    }
+}
@@ -0,0 +1,599 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: `as` picks the policy from the types: integers wrap, floats saturate
+//       towards integers. `Cast` names it instead, for every pair of
+//       primitive numeric types:
+//         1000i32.wrapping::<u8>()     // 232, as `as`
+//         1000i32.saturating::<u8>()   // 255
+//         1000i32.checked::<u8>()      // Err(1000_i32 is larger than u8::MAX)
+//         300.0f32.saturating::<u8>()  // 255, as `as`
+//         300.0f32.wrapping::<u8>()    // 44, what `to_int_unchecked` may return
+//         2.5f64.rounding::<u8>(Rounding::NearestEven)  // Ok(2)
+//       `checked` only succeeds when the value is exactly representable,
+//       e.g. `0.5f32.checked::<u8>()` and `16_777_217u32.checked::<f32>()`
+//       are errors
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CastError {
+    TooLarge {
+        value: String,
+        from: &'static str,
+        to: &'static str,
+    },
+    TooSmall {
+        value: String,
+        from: &'static str,
+        to: &'static str,
+    },
+    // A fraction or precision would be lost
+    Inexact {
+        value: String,
+        from: &'static str,
+        to: &'static str,
+    },
+    NaN {
+        from: &'static str,
+        to: &'static str,
+    },
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CastError::TooLarge { value, from, to } => {
+                write!(f, "{}_{} is larger than {}::MAX", value, from, to)
+            }
+            CastError::TooSmall { value, from, to } => {
+                write!(f, "{}_{} is smaller than {}::MIN", value, from, to)
+            }
+            CastError::Inexact { value, from, to } => {
+                write!(f, "{}_{} has no exact {} value", value, from, to)
+            }
+            CastError::NaN { from, to } => write!(f, "NaN_{} has no {} value", from, to),
+        }
+    }
+}
+
+impl error::Error for CastError {}
+
+// How `rounding` turns floats into integers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    // Halfway cases away from zero, as `f64::round`
+    Nearest,
+    // Halfway cases to the even integer, as `f64::round_ties_even`
+    NearestEven,
+    TowardZero,
+    Down,
+    Up,
+}
+
+// The conversions into `Self`, `Cast` is the more readable side
+pub trait CastFrom<S>: Sized {
+    fn checked_from(value: S) -> Result<Self, CastError>;
+
+    // Integers keep their low bits, as `as` does. Floats are truncated and
+    // wrapped the same way, NaN and infinities become 0
+    fn wrapping_from(value: S) -> Self;
+
+    // Out of range values become the bound crossed, NaN becomes 0
+    fn saturating_from(value: S) -> Self;
+}
+
+// Floats into integers only
+pub trait RoundFrom<S>: Sized {
+    fn rounding_from(value: S, mode: Rounding) -> Result<Self, CastError>;
+}
+
+pub trait Cast: Copy {
+    fn checked<T: CastFrom<Self>>(self) -> Result<T, CastError> {
+        T::checked_from(self)
+    }
+
+    fn wrapping<T: CastFrom<Self>>(self) -> T {
+        T::wrapping_from(self)
+    }
+
+    fn saturating<T: CastFrom<Self>>(self) -> T {
+        T::saturating_from(self)
+    }
+
+    fn rounding<T: RoundFrom<Self>>(self, mode: Rounding) -> Result<T, CastError> {
+        T::rounding_from(self, mode)
+    }
+}
+
+// What the macros need to know about the integer sources
+trait Integer: Copy {
+    fn is_negative(self) -> bool;
+
+    fn magnitude(self) -> u128;
+}
+
+macro_rules! impl_integer {
+    (signed: $($s:ident),*; unsigned: $($u:ident),*) => {
+        $(
+            impl Integer for $s {
+                fn is_negative(self) -> bool {
+                    self < 0
+                }
+
+                fn magnitude(self) -> u128 {
+                    self.unsigned_abs() as u128
+                }
+            }
+        )*
+        $(
+            impl Integer for $u {
+                fn is_negative(self) -> bool {
+                    false
+                }
+
+                fn magnitude(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(signed: i8, i16, i32, i64, i128, isize; unsigned: u8, u16, u32, u64, u128, usize);
+
+macro_rules! error {
+    ($kind:ident, $value:expr, $from:ident, $to:ident) => {
+        CastError::$kind {
+            value: format!("{:?}", $value),
+            from: stringify!($from),
+            to: stringify!($to),
+        }
+    };
+}
+
+// Every source with every target, `$targets` is a `[...]` list
+macro_rules! for_each_pair {
+    ($impl:ident, [$($from:ident),*], $targets:tt) => {
+        $(
+            $impl!($from, $targets);
+        )*
+    };
+}
+
+macro_rules! int_to_int {
+    ($from:ident, [$($to:ident),*]) => {
+        $(
+            impl CastFrom<$from> for $to {
+                fn checked_from(value: $from) -> Result<Self, CastError> {
+                    $to::try_from(value).map_err(|_| {
+                        if value.is_negative() {
+                            error!(TooSmall, value, $from, $to)
+                        } else {
+                            error!(TooLarge, value, $from, $to)
+                        }
+                    })
+                }
+
+                fn wrapping_from(value: $from) -> Self {
+                    value as $to
+                }
+
+                fn saturating_from(value: $from) -> Self {
+                    match $to::try_from(value) {
+                        Ok(value) => value,
+                        Err(_) if value.is_negative() => $to::MIN,
+                        Err(_) => $to::MAX,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! int_to_float {
+    ($from:ident, [$($to:ident),*]) => {
+        $(
+            impl CastFrom<$from> for $to {
+                // Exact when the significant bits fit in the mantissa
+                fn checked_from(value: $from) -> Result<Self, CastError> {
+                    let magnitude = value.magnitude();
+                    let significant_bits = if magnitude == 0 {
+                        0
+                    } else {
+                        128 - magnitude.leading_zeros() - magnitude.trailing_zeros()
+                    };
+
+                    let result = value as $to;
+                    if significant_bits <= $to::MANTISSA_DIGITS {
+                        Ok(result)
+                    } else if result.is_infinite() {
+                        Err(error!(TooLarge, value, $from, $to))
+                    } else {
+                        Err(error!(Inexact, value, $from, $to))
+                    }
+                }
+
+                // Rounded to the nearest float, as `as` does
+                fn wrapping_from(value: $from) -> Self {
+                    value as $to
+                }
+
+                // Only `u128` values above `f32::MAX` can round to infinity
+                fn saturating_from(value: $from) -> Self {
+                    let result = value as $to;
+                    if result.is_infinite() {
+                        $to::MAX
+                    } else {
+                        result
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! float_to_int {
+    ($from:ident, [$($to:ident),*]) => {
+        $(
+            impl CastFrom<$from> for $to {
+                fn checked_from(value: $from) -> Result<Self, CastError> {
+                    // Powers of two, so exact or infinite
+                    let min = $to::MIN as $from;
+                    let end = if $to::MIN == 0 {
+                        (2.0 as $from).powi($to::BITS as i32)
+                    } else {
+                        -min
+                    };
+
+                    if value.is_nan() {
+                        Err(CastError::NaN {
+                            from: stringify!($from),
+                            to: stringify!($to),
+                        })
+                    } else if value < min {
+                        Err(error!(TooSmall, value, $from, $to))
+                    } else if value >= end {
+                        Err(error!(TooLarge, value, $from, $to))
+                    } else if value.fract() != 0.0 {
+                        Err(error!(Inexact, value, $from, $to))
+                    } else {
+                        Ok(value as $to)
+                    }
+                }
+
+                // Modulo 2^BITS: below 2^127 the truncated value fits in
+                // `i128`, whose `as` wraps, above it's a multiple of 2^75,
+                // which `rem_euclid` reduces exactly
+                fn wrapping_from(value: $from) -> Self {
+                    if !value.is_finite() {
+                        return 0;
+                    }
+
+                    let value = (value as f64).trunc();
+                    if value.abs() < 2f64.powi(127) {
+                        value as i128 as $to
+                    } else {
+                        let modulus = 2f64.powi($to::BITS as i32);
+                        value.rem_euclid(modulus) as u128 as $to
+                    }
+                }
+
+                fn saturating_from(value: $from) -> Self {
+                    value as $to
+                }
+            }
+
+            impl RoundFrom<$from> for $to {
+                fn rounding_from(value: $from, mode: Rounding) -> Result<Self, CastError> {
+                    let rounded = match mode {
+                        Rounding::Nearest => value.round(),
+                        Rounding::NearestEven => value.round_ties_even(),
+                        Rounding::TowardZero => value.trunc(),
+                        Rounding::Down => value.floor(),
+                        Rounding::Up => value.ceil(),
+                    };
+
+                    // The error shows the value before rounding
+                    $to::checked_from(rounded).map_err(|e| match e {
+                        CastError::TooLarge { .. } => error!(TooLarge, value, $from, $to),
+                        CastError::TooSmall { .. } => error!(TooSmall, value, $from, $to),
+                        e => e,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! float_to_float {
+    ($from:ident, [$($to:ident),*]) => {
+        $(
+            impl CastFrom<$from> for $to {
+                // NaN stays NaN, infinities stay infinite
+                fn checked_from(value: $from) -> Result<Self, CastError> {
+                    let result = value as $to;
+
+                    if result.is_infinite() && value.is_finite() {
+                        if value > 0.0 {
+                            Err(error!(TooLarge, value, $from, $to))
+                        } else {
+                            Err(error!(TooSmall, value, $from, $to))
+                        }
+                    } else if !value.is_nan() && result as $from != value {
+                        Err(error!(Inexact, value, $from, $to))
+                    } else {
+                        Ok(result)
+                    }
+                }
+
+                fn wrapping_from(value: $from) -> Self {
+                    value as $to
+                }
+
+                fn saturating_from(value: $from) -> Self {
+                    let result = value as $to;
+
+                    match result {
+                        _ if value.is_infinite() || !result.is_infinite() => result,
+                        _ if value > 0.0 => $to::MAX,
+                        _ => $to::MIN,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+for_each_pair!(
+    int_to_int,
+    [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize],
+    [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize]
+);
+for_each_pair!(
+    int_to_float,
+    [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize],
+    [f32, f64]
+);
+for_each_pair!(
+    float_to_int,
+    [f32, f64],
+    [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize]
+);
+for_each_pair!(float_to_float, [f32, f64], [f32, f64]);
+
+macro_rules! impl_cast {
+    ($($t:ident),*) => {
+        $(
+            impl Cast for $t {}
+        )*
+    };
+}
+
+impl_cast!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every `u8` and `i8` into every type, against `as` and `TryFrom`
+    macro_rules! assert_small_ints {
+        ($($to:ident),*) => {
+            for value in u8::MIN..=u8::MAX {
+                $(
+                    assert_eq!(value.wrapping::<$to>(), value as $to);
+                    assert_eq!(value.checked::<$to>().ok(), $to::try_from(value).ok());
+                )*
+            }
+            for value in i8::MIN..=i8::MAX {
+                $(
+                    assert_eq!(value.wrapping::<$to>(), value as $to);
+                    assert_eq!(value.checked::<$to>().ok(), $to::try_from(value).ok());
+                )*
+            }
+        };
+    }
+
+    #[test]
+    fn test_small_ints_against_as() {
+        assert_small_ints!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+        for value in u8::MIN..=u8::MAX {
+            assert_eq!(value.checked::<f32>(), Ok(value as f32));
+            assert_eq!(value.saturating::<f64>(), value as f64);
+        }
+        for value in i8::MIN..=i8::MAX {
+            assert_eq!(value.checked::<f64>(), Ok(value as f64));
+            assert_eq!(value.saturating::<f32>(), value as f32);
+        }
+    }
+
+    // Every `i16` and `u16` into `u8` and `i8`
+    #[test]
+    fn test_into_small_ints() {
+        for value in i16::MIN..=i16::MAX {
+            assert_eq!(value.wrapping::<u8>(), value as u8);
+            assert_eq!(value.wrapping::<i8>(), value as i8);
+            assert_eq!(value.saturating::<u8>(), value.clamp(0, 255) as u8);
+            assert_eq!(value.saturating::<i8>(), value.clamp(-128, 127) as i8);
+            assert_eq!(value.checked::<u8>().ok(), u8::try_from(value).ok());
+            assert_eq!(value.checked::<i8>().ok(), i8::try_from(value).ok());
+        }
+        for value in u16::MIN..=u16::MAX {
+            assert_eq!(value.wrapping::<i8>(), value as i8);
+            assert_eq!(value.saturating::<i8>(), value.min(127) as i8);
+            assert_eq!(value.checked::<u8>().ok(), u8::try_from(value).ok());
+        }
+    }
+
+    // Floats around the `u8` and `i8` ranges, by quarters
+    #[test]
+    fn test_floats_into_small_ints() {
+        let specials = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -0.0];
+        let quarters = (-2000..=2000).map(|i| i as f32 / 4.0);
+
+        for value in quarters.chain(specials.iter().copied()) {
+            assert_eq!(value.saturating::<u8>(), value as u8);
+            assert_eq!(value.saturating::<i8>(), value as i8);
+            assert_eq!((value as f64).saturating::<u8>(), value as u8);
+
+            let exact_u8 = value.fract() == 0.0 && (0.0..=255.0).contains(&value);
+            let exact_i8 = value.fract() == 0.0 && (-128.0..=127.0).contains(&value);
+            assert_eq!(
+                value.checked::<u8>().ok(),
+                Some(value as u8).filter(|_| exact_u8)
+            );
+            assert_eq!(
+                value.checked::<i8>().ok(),
+                Some(value as i8).filter(|_| exact_i8)
+            );
+
+            // Truncated, then wrapped as integers are
+            if value.is_finite() {
+                assert_eq!(value.wrapping::<u8>(), (value as i32) as u8);
+                assert_eq!(value.wrapping::<i8>(), (value as i32) as i8);
+            } else {
+                assert_eq!(value.wrapping::<u8>(), 0);
+            }
+        }
+    }
+
+    // The 5.1 examples
+    #[test]
+    fn test_section_examples() {
+        assert_eq!(1000i32.wrapping::<u16>(), 1000);
+        assert_eq!(1000i32.wrapping::<u8>(), 232);
+        assert_eq!((-1i8).wrapping::<u8>(), 255);
+        assert_eq!(128i32.wrapping::<i8>(), -128);
+        assert_eq!(232i32.wrapping::<i8>(), -24);
+        assert_eq!(300.0f32.saturating::<u8>(), 255);
+        assert_eq!((-100.0f32).saturating::<u8>(), 0);
+        assert_eq!(f32::NAN.saturating::<u8>(), 0);
+
+        // The values `to_int_unchecked` happened to return, but sound
+        assert_eq!(300.0f32.wrapping::<u8>(), 44);
+        assert_eq!((-100.0f32).wrapping::<u8>(), 156);
+    }
+
+    #[test]
+    fn test_wide_types() {
+        assert_eq!(u128::MAX.wrapping::<f32>(), f32::INFINITY);
+        assert_eq!(u128::MAX.saturating::<f32>(), f32::MAX);
+        assert_eq!(i128::MIN.checked::<f32>(), Ok(-(2f32.powi(127))));
+        assert_eq!(u64::MAX.saturating::<i64>(), i64::MAX);
+        assert_eq!(i128::MIN.saturating::<usize>(), 0);
+        assert_eq!((1u64 << 53).checked::<f64>(), Ok(9007199254740992.0));
+        assert!(matches!(
+            ((1u64 << 53) + 1).checked::<f64>(),
+            Err(CastError::Inexact { .. })
+        ));
+        assert!(matches!(
+            u128::MAX.checked::<f32>(),
+            Err(CastError::TooLarge { .. })
+        ));
+
+        assert_eq!(1e300f64.saturating::<f32>(), f32::MAX);
+        assert_eq!((-1e300f64).saturating::<f32>(), f32::MIN);
+        assert_eq!(f64::INFINITY.saturating::<f32>(), f32::INFINITY);
+        assert_eq!(0.5f64.checked::<f32>(), Ok(0.5));
+        assert!(f64::NAN.checked::<f32>().unwrap().is_nan());
+        assert!(matches!(
+            0.1f64.checked::<f32>(),
+            Err(CastError::Inexact { .. })
+        ));
+        assert_eq!(0.1f32.checked::<f64>(), Ok(0.1f32 as f64));
+
+        assert_eq!(1e20f64.saturating::<u64>(), u64::MAX);
+        assert_eq!(f32::MAX.checked::<u128>().ok(), Some(f32::MAX as u128));
+        assert_eq!((-1.0f64).wrapping::<u128>(), u128::MAX);
+        assert_eq!((-3.0f64).wrapping::<i64>(), -3);
+        assert_eq!((-3.0f64).wrapping::<u64>(), u64::MAX - 2);
+        assert_eq!((-3.0f64).wrapping::<i128>(), -3);
+        assert_eq!((-3.0f64).wrapping::<u128>(), u128::MAX - 2);
+        assert_eq!((-3.5f32).wrapping::<u64>(), u64::MAX - 2);
+        assert_eq!((-2f64.powi(127)).wrapping::<i128>(), i128::MIN);
+        assert_eq!(2f64.powi(127).wrapping::<i128>(), i128::MIN);
+        assert_eq!((-2f64.powi(127)).wrapping::<u128>(), 1 << 127);
+        assert_eq!(2f64.powi(128).wrapping::<u128>(), 0);
+        assert_eq!((-2f64.powi(200)).wrapping::<i64>(), 0);
+        assert_eq!(2f64.powi(64).wrapping::<u64>(), 0);
+        assert_eq!((2f64.powi(63) + 2048.0).wrapping::<i64>(), i64::MIN + 2048);
+    }
+
+    #[test]
+    fn test_rounding() {
+        let modes = [
+            Rounding::Nearest,
+            Rounding::NearestEven,
+            Rounding::TowardZero,
+            Rounding::Down,
+            Rounding::Up,
+        ];
+        let round = |value: f64| -> Vec<i8> {
+            modes
+                .iter()
+                .map(|&mode| value.rounding::<i8>(mode).unwrap())
+                .collect()
+        };
+
+        assert_eq!(round(2.5), [3, 2, 2, 2, 3]);
+        assert_eq!(round(-2.5), [-3, -2, -2, -3, -2]);
+        assert_eq!(round(3.5), [4, 4, 3, 3, 4]);
+        assert_eq!(round(-0.2), [0, 0, 0, -1, 0]);
+
+        assert_eq!(255.4f32.rounding::<u8>(Rounding::Nearest), Ok(255));
+        assert_eq!(
+            255.5f32.rounding::<u8>(Rounding::Nearest),
+            Err(CastError::TooLarge {
+                value: "255.5".to_owned(),
+                from: "f32",
+                to: "u8"
+            })
+        );
+        assert_eq!(
+            (-0.5f32)
+                .rounding::<u8>(Rounding::Down)
+                .unwrap_err()
+                .to_string(),
+            "-0.5_f32 is smaller than u8::MIN"
+        );
+        assert_eq!(
+            f64::NAN.rounding::<u8>(Rounding::Up),
+            Err(CastError::NaN {
+                from: "f64",
+                to: "u8"
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let message = |e: CastError| e.to_string();
+
+        assert_eq!(
+            message(1000i32.checked::<u8>().unwrap_err()),
+            "1000_i32 is larger than u8::MAX"
+        );
+        assert_eq!(
+            message((-1i8).checked::<u8>().unwrap_err()),
+            "-1_i8 is smaller than u8::MIN"
+        );
+        assert_eq!(
+            message(0.5f32.checked::<u8>().unwrap_err()),
+            "0.5_f32 has no exact u8 value"
+        );
+        assert_eq!(
+            message(16_777_217u32.checked::<f32>().unwrap_err()),
+            "16777217_u32 has no exact f32 value"
+        );
+        assert_eq!(
+            message(f32::NAN.checked::<i8>().unwrap_err()),
+            "NaN_f32 has no i8 value"
+        );
+        assert_eq!(
+            message(f64::INFINITY.checked::<i8>().unwrap_err()),
+            "inf_f64 is larger than i8::MAX"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the literals of 5.2 read at runtime, with the type the compiler
+//       would give them:
+//         parse_literal("1u8")      // Ok(Literal::U8(1)), 1 byte
+//         parse_literal("1")        // Ok(Literal::I32(1)), 4 bytes
+//         parse_literal("1.0")      // Ok(Literal::F64(1.0)), 8 bytes
+//         parse_literal("0xff_u16") // Ok(Literal::U16(255)), 2 bytes
+//         parse_literal("1e4f32")   // Ok(Literal::F32(10000.0)), 4 bytes
+//       A leading `-` negates the literal, so `-128i8` is in range as it is
+//       for the compiler
+//       The errors are the compiler's, e.g. `256u8` is out of range for `u8`
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseLiteralError {
+    Empty,
+    NoDigits,
+    InvalidDigit { digit: char, radix: u32 },
+    NoExponentDigits,
+    InvalidSuffix(String),
+    OutOfRange(&'static str),
+    // `-` on an unsigned type
+    Negated(&'static str),
+}
+
+impl fmt::Display for ParseLiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseLiteralError::Empty => write!(f, "empty literal"),
+            ParseLiteralError::NoDigits => write!(f, "no valid digits found for number"),
+            ParseLiteralError::InvalidDigit { digit, radix } => {
+                write!(f, "invalid digit `{}` for a base {} literal", digit, radix)
+            }
+            ParseLiteralError::NoExponentDigits => {
+                write!(f, "expected at least one digit in exponent")
+            }
+            ParseLiteralError::InvalidSuffix(suffix) => {
+                write!(f, "invalid suffix `{}` for number literal", suffix)
+            }
+            ParseLiteralError::OutOfRange(ty) => write!(f, "literal out of range for `{}`", ty),
+            ParseLiteralError::Negated(ty) => {
+                write!(f, "cannot apply unary operator `-` to type `{}`", ty)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseLiteralError {}
+
+macro_rules! literal {
+    (ints: $($int:ident($i:ident)),*; floats: $($float:ident($f:ident)),*) => {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum Literal {
+            $($int($i),)*
+            $($float($f),)*
+        }
+
+        impl Literal {
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    $(Literal::$int(_) => stringify!($i),)*
+                    $(Literal::$float(_) => stringify!($f),)*
+                }
+            }
+
+            // What `mem::size_of_val` would return for the value
+            pub fn size_of(&self) -> usize {
+                match self {
+                    $(Literal::$int(_) => mem::size_of::<$i>(),)*
+                    $(Literal::$float(_) => mem::size_of::<$f>(),)*
+                }
+            }
+        }
+
+        // A literal that parses back to the same value, e.g. `-1i8`
+        impl fmt::Display for Literal {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    $(Literal::$int(value) => write!(f, "{}{}", value, stringify!($i)),)*
+                    $(Literal::$float(value) => write!(f, "{:?}{}", value, stringify!($f)),)*
+                }
+            }
+        }
+
+        fn int_literal(
+            magnitude: u128,
+            negative: bool,
+            suffix: &str,
+        ) -> Result<Literal, ParseLiteralError> {
+            // `-170141183460469231731687303715884105728i128` is in range
+            let negated = if magnitude == 1 << 127 {
+                Some(i128::MIN)
+            } else {
+                i128::try_from(magnitude).ok().map(|value| -value)
+            };
+
+            $(
+                if suffix == stringify!($i) {
+                    let value = if !negative {
+                        $i::try_from(magnitude).ok()
+                    } else if $i::MIN == 0 {
+                        return Err(ParseLiteralError::Negated(stringify!($i)));
+                    } else {
+                        negated.and_then(|value| $i::try_from(value).ok())
+                    };
+
+                    return value
+                        .map(Literal::$int)
+                        .ok_or(ParseLiteralError::OutOfRange(stringify!($i)));
+                }
+            )*
+
+            Err(ParseLiteralError::InvalidSuffix(suffix.to_owned()))
+        }
+
+        // `digits` is a valid float without underscores
+        fn float_literal(
+            digits: &str,
+            negative: bool,
+            suffix: &str,
+        ) -> Result<Literal, ParseLiteralError> {
+            $(
+                if suffix == stringify!($f) {
+                    let value: $f = digits
+                        .parse()
+                        .map_err(|_| ParseLiteralError::NoDigits)?;
+
+                    if value.is_infinite() {
+                        return Err(ParseLiteralError::OutOfRange(stringify!($f)));
+                    }
+
+                    return Ok(Literal::$float(if negative { -value } else { value }));
+                }
+            )*
+
+            Err(ParseLiteralError::InvalidSuffix(suffix.to_owned()))
+        }
+    };
+}
+
+literal!(
+    ints: I8(i8), I16(i16), I32(i32), I64(i64), I128(i128), Isize(isize),
+        U8(u8), U16(u16), U32(u32), U64(u64), U128(u128), Usize(usize);
+    floats: F32(f32), F64(f64)
+);
+
+pub fn parse_literal(s: &str) -> Result<Literal, ParseLiteralError> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if s.is_empty() {
+        return Err(ParseLiteralError::Empty);
+    }
+
+    let radix = match s.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+
+    if radix != 10 {
+        // Hex digits include `f`, so only integer suffixes exist
+        let rest = &s[2..];
+        let suffix_start = rest.find(['i', 'u']).unwrap_or(rest.len());
+        let (digits, suffix) = rest.split_at(suffix_start);
+
+        let magnitude = int_digits(digits, radix)?;
+        return int_literal(magnitude, negative, or_default(suffix, "i32"));
+    }
+
+    if !s.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(ParseLiteralError::NoDigits);
+    }
+
+    let bytes = s.as_bytes();
+    let digits_len = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b'_')
+            .count()
+    };
+
+    let mut end = digits_len(0);
+    let mut is_float = false;
+
+    // `1.5` and `1.`, but `1.f32` would be a field access
+    if bytes.get(end) == Some(&b'.') {
+        if bytes
+            .get(end + 1)
+            .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+        {
+            return Err(ParseLiteralError::InvalidSuffix(s[end + 1..].to_owned()));
+        }
+
+        is_float = true;
+        end += 1 + digits_len(end + 1);
+    }
+
+    if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'+') | Some(b'-')) {
+            exponent += 1;
+        }
+
+        let len = digits_len(exponent);
+        if !bytes[exponent..exponent + len]
+            .iter()
+            .any(u8::is_ascii_digit)
+        {
+            return Err(ParseLiteralError::NoExponentDigits);
+        }
+
+        is_float = true;
+        end = exponent + len;
+    }
+
+    let digits = s[..end].replace('_', "");
+    let suffix = &s[end..];
+
+    if is_float || suffix == "f32" || suffix == "f64" {
+        float_literal(&digits, negative, or_default(suffix, "f64"))
+    } else {
+        int_literal(
+            int_digits(&digits, 10)?,
+            negative,
+            or_default(suffix, "i32"),
+        )
+    }
+}
+
+// The compiler's defaults for unsuffixed literals
+fn or_default<'a>(suffix: &'a str, default: &'a str) -> &'a str {
+    if suffix.is_empty() {
+        default
+    } else {
+        suffix
+    }
+}
+
+fn int_digits(digits: &str, radix: u32) -> Result<u128, ParseLiteralError> {
+    let digits = digits.replace('_', "");
+
+    if digits.is_empty() {
+        return Err(ParseLiteralError::NoDigits);
+    }
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix)) {
+        return Err(ParseLiteralError::InvalidDigit { digit, radix });
+    }
+
+    // Larger than any type
+    u128::from_str_radix(&digits, radix).map_err(|_| ParseLiteralError::OutOfRange("u128"))
+}
+
+impl FromStr for Literal {
+    type Err = ParseLiteralError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_literal(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The literals of 5.2
+    #[test]
+    fn test_section_sizes() {
+        let sizes: Vec<_> = ["1u8", "2u32", "3f32", "1", "1.0"]
+            .iter()
+            .map(|s| parse_literal(s).unwrap().size_of())
+            .collect();
+
+        assert_eq!(sizes, [1, 4, 4, 4, 8]);
+        assert_eq!(parse_literal("1"), Ok(Literal::I32(1)));
+        assert_eq!(parse_literal("1.0"), Ok(Literal::F64(1.0)));
+        assert_eq!(parse_literal("3f32"), Ok(Literal::F32(3.0)));
+    }
+
+    #[test]
+    fn test_forms() {
+        assert_eq!(parse_literal("0xff"), Ok(Literal::I32(255)));
+        assert_eq!(parse_literal("0xffu8"), Ok(Literal::U8(255)));
+        assert_eq!(parse_literal("0x1f32"), Ok(Literal::I32(0x1f32)));
+        assert_eq!(parse_literal("0o777_i16"), Ok(Literal::I16(0o777)));
+        assert_eq!(parse_literal("0b1000_0000u8"), Ok(Literal::U8(128)));
+        assert_eq!(parse_literal("1_000_000u64"), Ok(Literal::U64(1_000_000)));
+        assert_eq!(parse_literal("7usize"), Ok(Literal::Usize(7)));
+        assert_eq!(parse_literal("1e4"), Ok(Literal::F64(1e4)));
+        assert_eq!(parse_literal("2.5E-3_f32"), Ok(Literal::F32(2.5e-3)));
+        assert_eq!(parse_literal("1."), Ok(Literal::F64(1.0)));
+        assert_eq!(parse_literal("1_2.3_4"), Ok(Literal::F64(12.34)));
+        assert_eq!(parse_literal("0.1f32"), Ok(Literal::F32(0.1)));
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(parse_literal("-128i8"), Ok(Literal::I8(-128)));
+        assert_eq!(parse_literal("-1"), Ok(Literal::I32(-1)));
+        assert_eq!(
+            parse_literal("-170141183460469231731687303715884105728i128"),
+            Ok(Literal::I128(i128::MIN))
+        );
+        assert_eq!(
+            parse_literal("340282366920938463463374607431768211455u128"),
+            Ok(Literal::U128(u128::MAX))
+        );
+        assert_eq!(
+            parse_literal("128i8"),
+            Err(ParseLiteralError::OutOfRange("i8"))
+        );
+        assert_eq!(
+            parse_literal("256u8"),
+            Err(ParseLiteralError::OutOfRange("u8"))
+        );
+        assert_eq!(
+            parse_literal("3_000_000_000"),
+            Err(ParseLiteralError::OutOfRange("i32"))
+        );
+        assert_eq!(
+            parse_literal("340282366920938463463374607431768211456u128"),
+            Err(ParseLiteralError::OutOfRange("u128"))
+        );
+        assert_eq!(
+            parse_literal("1e39f32"),
+            Err(ParseLiteralError::OutOfRange("f32"))
+        );
+        assert_eq!(parse_literal("-1u8"), Err(ParseLiteralError::Negated("u8")));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s: &str| parse_literal(s).unwrap_err().to_string();
+
+        assert_eq!(error(""), "empty literal");
+        assert_eq!(error("0x"), "no valid digits found for number");
+        assert_eq!(error("0x_u8"), "no valid digits found for number");
+        assert_eq!(error("_1"), "no valid digits found for number");
+        assert_eq!(error("0b102"), "invalid digit `2` for a base 2 literal");
+        assert_eq!(error("0o8"), "invalid digit `8` for a base 8 literal");
+        assert_eq!(error("1e"), "expected at least one digit in exponent");
+        assert_eq!(error("1e_f32"), "expected at least one digit in exponent");
+        assert_eq!(error("1u7"), "invalid suffix `u7` for number literal");
+        assert_eq!(error("1.0u8"), "invalid suffix `u8` for number literal");
+        assert_eq!(error("1.f32"), "invalid suffix `f32` for number literal");
+        assert_eq!(error("1.0f16"), "invalid suffix `f16` for number literal");
+        assert_eq!(error("0x1.0"), "invalid digit `.` for a base 16 literal");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for s in [
+            "-1i8",
+            "255u8",
+            "1f32",
+            "0.1f64",
+            "-2.5e-3f32",
+            "0xffff_u16",
+            "1",
+        ] {
+            let literal: Literal = s.parse().unwrap();
+
+            assert_eq!(literal.to_string().parse(), Ok(literal));
+        }
+
+        assert_eq!(parse_literal("0xffu8").unwrap().to_string(), "255u8");
+        assert_eq!(parse_literal("1e4").unwrap().to_string(), "10000.0f64");
+    }
+}
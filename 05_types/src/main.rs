@@ -10,6 +10,10 @@
 // Suppress all warnings from casts which overflow.
 #![allow(overflowing_literals)]
 
+mod cast;
+mod literal;
+mod quantity;
+
 fn main() {
     // https://doc.rust-lang.org/rust-by-example/types.html
     println!();
@@ -92,6 +96,48 @@ fn main() {
             // nan as u8 is 0
             println!("nan as u8 is {}", f32::NAN.to_int_unchecked::<u8>());
         }
+
+        // NOTE: `cast.rs` names the policy instead of relying on the types
+        {
+            use cast::{Cast, Rounding};
+
+            println!("1000 wrapping to u8 is    {}", 1000.wrapping::<u8>());
+            println!("1000 saturating to u8 is  {}", 1000.saturating::<u8>());
+            match 1000.checked::<u8>() {
+                Ok(value) => println!("1000 checked to u8 is     {}", value),
+                Err(e) => println!("1000 checked to u8 fails: {}", e),
+            }
+
+            // Soundly what `to_int_unchecked` returned above
+            println!("300.0 wrapping to u8 is   {}", 300.0_f32.wrapping::<u8>());
+            println!(
+                "-100.0 wrapping to u8 is  {}",
+                (-100.0_f32).wrapping::<u8>()
+            );
+            println!("300.0 saturating to u8 is {}", 300.0_f32.saturating::<u8>());
+            if let Err(e) = f32::NAN.checked::<u8>() {
+                println!("nan checked to u8 fails:  {}", e);
+            }
+            if let Err(e) = 16_777_217_u32.checked::<f32>() {
+                println!("16777217 checked to f32 fails: {}", e);
+            }
+
+            for &mode in &[
+                Rounding::Nearest,
+                Rounding::NearestEven,
+                Rounding::TowardZero,
+                Rounding::Down,
+                Rounding::Up,
+            ] {
+                println!(
+                    "{:?}: 2.5 -> {:?}, -2.5 -> {:?}, 255.5 -> {:?}",
+                    mode,
+                    2.5.rounding::<i32>(mode),
+                    (-2.5).rounding::<i32>(mode),
+                    255.5.rounding::<u8>(mode).map_err(|e| e.to_string()),
+                );
+            }
+        }
     }
 
     // https://doc.rust-lang.org/rust-by-example/types/literals.html
@@ -113,6 +159,30 @@ fn main() {
         println!("size of `z` in bytes: {}", std::mem::size_of_val(&z));
         println!("size of `i` in bytes: {}", std::mem::size_of_val(&i));
         println!("size of `f` in bytes: {}", std::mem::size_of_val(&f));
+
+        // NOTE: the same for literals only known at runtime
+        for s in &[
+            "1u8",
+            "2u32",
+            "3f32",
+            "1",
+            "1.0",
+            "0xffff_u16",
+            "1e4",
+            "-128i8",
+            "256u8",
+        ] {
+            match literal::parse_literal(s) {
+                Ok(literal) => println!(
+                    "size of `{}` in bytes: {} (`{}` of type `{}`)",
+                    s,
+                    literal.size_of(),
+                    literal,
+                    literal.type_name()
+                ),
+                Err(e) => println!("`{}` is not a literal: {}", s, e),
+            }
+        }
     }
 
     // https://doc.rust-lang.org/rust-by-example/types/inference.html
@@ -140,27 +210,40 @@ fn main() {
     println!();
     println!("--- 5.4. Aliasing ---");
     {
-        // `NanoSecond` is a new name for `u64`.
-        type NanoSecond = u64;
-        type Inch = u64;
+        use std::time::Duration;
+
+        // NOTE: `NanoSecond` and `Inch` were aliases of `u64`, they are
+        //       `quantity!` newtypes now, see `quantity.rs`
+        use quantity::{Inch, Millimetre, NanoSecond};
 
         // Use an attribute to silence warning.
-        // #[allow(non_camel_case_types)]
-        // type u64_t = u64;
+        #[allow(non_camel_case_types)]
+        type u64_t = u64;
         // DONE: ^ Try removing the attribute
         //       NOTE: warning will be produced
 
-        // `NanoSecond` = `Inch` = `u64_t` = `u64`.
-        let nanoseconds: NanoSecond = 5 as u64_t;
-        let inches: Inch = 2 as u64_t;
+        // `u64_t` = `u64`, but `NanoSecond` and `Inch` are new types
+        let nanoseconds = NanoSecond(5 as u64_t);
+        let inches = Inch(2 as u64_t);
 
         // Note that type aliases *don't* provide any extra type safety, because
         // aliases are *not* new types
+        // NOTE: newtypes do, this is now a compile error:
+        // println!(
+        //     "{} nanoseconds + {} inches = {} unit?",
+        //     nanoseconds,
+        //     inches,
+        //     nanoseconds + inches
+        // );
         println!(
-            "{} nanoseconds + {} inches = {} unit?",
+            "{} + {} = {}",
             nanoseconds,
-            inches,
-            nanoseconds + inches
+            NanoSecond(2),
+            nanoseconds + NanoSecond(2)
         );
+        println!("{} = {:?}", nanoseconds, Duration::from(nanoseconds));
+        println!("{} = {}", inches, Millimetre::from(inches));
+        println!("{} * 3 = {}", inches, inches * 3);
+        println!("{} / {} = {}", inches * 3, inches, inches * 3 / inches);
     }
 }
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the 5.4 `type NanoSecond = u64` as a newtype, so units don't mix:
+//         NanoSecond(5) + NanoSecond(2)  // NanoSecond(7), "7 ns"
+//         NanoSecond(5) + Inch(2)        // Error! mismatched types
+//         NanoSecond(5) * 3              // NanoSecond(15), scaling
+//         NanoSecond(6) / NanoSecond(2)  // 3, a plain ratio
+//       Changing units is explicit, through `From`:
+//         Duration::from(NanoSecond(5))
+//         Millimetre::from(Inch(2))      // Millimetre(50.8)
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::Sum;
+use std::num::TryFromIntError;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::time::Duration;
+
+// A `$name($inner)` newtype with arithmetic between the same units only,
+// `Display` ends with `$unit`
+macro_rules! quantity {
+    ($(#[$attr:meta])* $vis:vis $name:ident($inner:ty), $unit:literal) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+        $vis struct $name(pub $inner);
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, rhs: $name) -> $name {
+                $name(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: $name) -> $name {
+                $name(self.0 - rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: $name) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: $name) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        // Scaling by a plain number keeps the unit
+        impl Mul<$inner> for $name {
+            type Output = $name;
+
+            fn mul(self, rhs: $inner) -> $name {
+                $name(self.0 * rhs)
+            }
+        }
+
+        impl Div<$inner> for $name {
+            type Output = $name;
+
+            fn div(self, rhs: $inner) -> $name {
+                $name(self.0 / rhs)
+            }
+        }
+
+        // The units cancel out
+        impl Div for $name {
+            type Output = $inner;
+
+            fn div(self, rhs: $name) -> $inner {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = $name>>(iter: I) -> $name {
+                iter.fold($name::default(), Add::add)
+            }
+        }
+
+        // The flags, e.g. `{:.1}`, apply to the number
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)?;
+                write!(f, " {}", $unit)
+            }
+        }
+    };
+}
+
+quantity!(pub NanoSecond(u64), "ns");
+quantity!(pub Inch(u64), "in");
+quantity!(pub Millimetre(f64), "mm");
+
+impl From<NanoSecond> for Duration {
+    fn from(nanoseconds: NanoSecond) -> Duration {
+        Duration::from_nanos(nanoseconds.0)
+    }
+}
+
+// `u64` nanoseconds end after about 584 years, `Duration`s don't
+impl TryFrom<Duration> for NanoSecond {
+    type Error = TryFromIntError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        u64::try_from(duration.as_nanos()).map(NanoSecond)
+    }
+}
+
+impl From<Inch> for Millimetre {
+    fn from(inches: Inch) -> Millimetre {
+        Millimetre(inches.0 as f64 * 25.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut total = NanoSecond(5) + NanoSecond(2);
+        assert_eq!(total, NanoSecond(7));
+
+        total -= NanoSecond(1);
+        total += NanoSecond(4);
+        assert_eq!(total, NanoSecond(10));
+        assert_eq!(total - NanoSecond(3), NanoSecond(7));
+        assert_eq!(total * 3, NanoSecond(30));
+        assert_eq!(total / 4, NanoSecond(2));
+        assert_eq!(total / NanoSecond(5), 2);
+        assert!(Inch(1) < Inch(2));
+        assert_eq!(
+            vec![Inch(1), Inch(2), Inch(3)].into_iter().sum::<Inch>(),
+            Inch(6)
+        );
+        assert_eq!(Vec::<Inch>::new().into_iter().sum::<Inch>(), Inch(0));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            Duration::from(NanoSecond(1_500)),
+            Duration::from_micros(3) / 2
+        );
+        assert_eq!(
+            NanoSecond::try_from(Duration::from_secs(2)),
+            Ok(NanoSecond(2_000_000_000))
+        );
+        assert!(NanoSecond::try_from(Duration::MAX).is_err());
+        assert_eq!(Millimetre::from(Inch(2)), Millimetre(50.8));
+        assert_eq!(Millimetre::from(Inch(0)), Millimetre(0.0));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(NanoSecond(7).to_string(), "7 ns");
+        assert_eq!(Inch(2).to_string(), "2 in");
+        assert_eq!(format!("{:.1}", Millimetre(50.8)), "50.8 mm");
+        assert_eq!(format!("{:>4}", Inch(2)), "   2 in");
+    }
+}
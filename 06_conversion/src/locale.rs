@@ -0,0 +1,616 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: `"5".parse::<i32>()` of 6.3 for numbers written by people, a
+//       `NumberParser` knows the separators of a locale:
+//         NumberParser::ENGLISH.parse::<f64>("1,234.5")   // Ok(1234.5)
+//         NumberParser::GERMAN.parse::<f64>("1.234,5")    // Ok(1234.5)
+//         NumberParser::FRENCH.parse::<f64>("1 234,5")    // Ok(1234.5)
+//         NumberParser::ENGLISH.parse::<f64>("-12.5%")    // Ok(-0.125)
+//         NumberParser::ENGLISH.parse::<i32>("١٢٣")       // Ok(123)
+//       and writes them back the same way:
+//         NumberParser::GERMAN.format(1234.5)             // "1.234,5"
+//         NumberParser::ENGLISH.format_percent(-0.125)    // "-12.5%"
+//       Errors have the byte offset of what failed:
+//         NumberParser::ENGLISH.parse::<f64>("12,34")     // byte 2: ...
+
+use std::any;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// The zero of each run of decimal digits of the Basic Multilingual Plane,
+// the nine others follow it
+const DIGIT_ZEROS: [char; 37] = [
+    '0',        // ASCII
+    '\u{0660}', // Arabic-Indic
+    '\u{06F0}', // Extended Arabic-Indic
+    '\u{07C0}', // NKo
+    '\u{0966}', // Devanagari
+    '\u{09E6}', // Bengali
+    '\u{0A66}', // Gurmukhi
+    '\u{0AE6}', // Gujarati
+    '\u{0B66}', // Oriya
+    '\u{0BE6}', // Tamil
+    '\u{0C66}', // Telugu
+    '\u{0CE6}', // Kannada
+    '\u{0D66}', // Malayalam
+    '\u{0DE6}', // Sinhala Lith
+    '\u{0E50}', // Thai
+    '\u{0ED0}', // Lao
+    '\u{0F20}', // Tibetan
+    '\u{1040}', // Myanmar
+    '\u{1090}', // Myanmar Shan
+    '\u{17E0}', // Khmer
+    '\u{1810}', // Mongolian
+    '\u{1946}', // Limbu
+    '\u{19D0}', // New Tai Lue
+    '\u{1A80}', // Tai Tham Hora
+    '\u{1A90}', // Tai Tham Tham
+    '\u{1B50}', // Balinese
+    '\u{1BB0}', // Sundanese
+    '\u{1C40}', // Lepcha
+    '\u{1C50}', // Ol Chiki
+    '\u{A620}', // Vai
+    '\u{A8D0}', // Saurashtra
+    '\u{A900}', // Kayah Li
+    '\u{A9D0}', // Javanese
+    '\u{A9F0}', // Myanmar Tai Laing
+    '\u{AA50}', // Cham
+    '\u{ABF0}', // Meetei Mayek
+    '\u{FF10}', // Fullwidth
+];
+
+// The zero of the script of `c` and its value
+fn decimal_digit(c: char) -> Option<(char, u8)> {
+    DIGIT_ZEROS.iter().find_map(|&zero| {
+        let value = (c as u32).checked_sub(zero as u32)?;
+        if value < 10 {
+            Some((zero, value as u8))
+        } else {
+            None
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberParser {
+    // Between groups of 3 digits, optional when parsing
+    grouping: Option<char>,
+    decimal: char,
+    // Of the digits `format` writes, any are parsed
+    zero: char,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseNumberError {
+    Empty,
+    // Only a sign or a percent
+    NoDigits {
+        offset: usize,
+    },
+    InvalidCharacter {
+        offset: usize,
+        character: char,
+    },
+    // Not between groups of 3 digits of the integer part
+    MisplacedGroupSeparator {
+        offset: usize,
+    },
+    MultipleDecimalSeparators {
+        offset: usize,
+    },
+    // e.g. ASCII and Devanagari digits in the same number
+    MixedDigits {
+        offset: usize,
+    },
+    // Rejected by the `FromStr` of the type, e.g. too large, or a fraction for
+    // an integer, `offset` is the start of the digits
+    NotRepresentable {
+        offset: usize,
+        type_name: &'static str,
+    },
+}
+
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseNumberError::Empty => write!(f, "cannot parse a number from an empty string"),
+            ParseNumberError::NoDigits { offset } => {
+                write!(f, "byte {}: expected a digit", offset)
+            }
+            ParseNumberError::InvalidCharacter { offset, character } => {
+                write!(f, "byte {}: invalid character `{}`", offset, character)
+            }
+            ParseNumberError::MisplacedGroupSeparator { offset } => write!(
+                f,
+                "byte {}: group separators must be between groups of 3 digits",
+                offset
+            ),
+            ParseNumberError::MultipleDecimalSeparators { offset } => {
+                write!(f, "byte {}: second decimal separator", offset)
+            }
+            ParseNumberError::MixedDigits { offset } => {
+                write!(f, "byte {}: digit from another script", offset)
+            }
+            ParseNumberError::NotRepresentable { offset, type_name } => {
+                write!(f, "byte {}: not a valid `{}`", offset, type_name)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseNumberError {}
+
+// `digits` in ASCII with the decimal point before `digits[point]`
+struct Decimal {
+    negative: bool,
+    digits: String,
+    point: usize,
+}
+
+impl Decimal {
+    // From a `Display`ed number, `None` for e.g. `NaN` or `inf`
+    fn from_display(s: &str) -> Option<Decimal> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return None;
+        }
+
+        Some(Decimal {
+            negative,
+            digits: format!("{}{}", integer, fraction),
+            point: integer.len(),
+        })
+    }
+
+    // Times 100 for `true`, divided by 100 for `false`, exactly
+    fn shift(mut self, right: bool) -> Decimal {
+        if right {
+            while self.digits.len() < self.point + 2 {
+                self.digits.push('0');
+            }
+            self.point += 2;
+        } else {
+            while self.point < 2 {
+                self.digits.insert(0, '0');
+                self.point += 1;
+            }
+            self.point -= 2;
+        }
+
+        self
+    }
+
+    // Without the zeros that don't count, with a `0` before the point
+    fn parts(&self) -> (&str, &str) {
+        let (integer, fraction) = self.digits.split_at(self.point);
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+
+        (if integer.is_empty() { "0" } else { integer }, fraction)
+    }
+
+    // For `FromStr`, e.g. `-1234.5`, integers have no point
+    fn to_ascii(&self) -> String {
+        let (integer, fraction) = self.parts();
+        let sign = if self.negative { "-" } else { "" };
+
+        if fraction.is_empty() {
+            format!("{}{}", sign, integer)
+        } else {
+            format!("{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+impl NumberParser {
+    // No grouping, as `str::parse`
+    pub const PLAIN: NumberParser = NumberParser::new(None, '.');
+    pub const ENGLISH: NumberParser = NumberParser::new(Some(','), '.');
+    pub const GERMAN: NumberParser = NumberParser::new(Some('.'), ',');
+    pub const FRENCH: NumberParser = NumberParser::new(Some(' '), ',');
+
+    // Panics if the separators are the same, or a digit, sign or `%`
+    pub const fn new(grouping: Option<char>, decimal: char) -> NumberParser {
+        if let Some(grouping) = grouping {
+            assert!(grouping != decimal, "the separators must differ");
+            assert!(is_separator(grouping), "invalid grouping separator");
+        }
+        assert!(is_separator(decimal), "invalid decimal separator");
+
+        NumberParser {
+            grouping,
+            decimal,
+            zero: '0',
+        }
+    }
+
+    // The digits of `format`, panics if `zero` isn't the zero of a script
+    pub fn with_digits(self, zero: char) -> NumberParser {
+        assert!(
+            DIGIT_ZEROS.contains(&zero),
+            "`{}` is not a zero digit",
+            zero
+        );
+
+        NumberParser { zero, ..self }
+    }
+
+    // Through `T::from_str`, so any number type, `50%` is `0.5`
+    pub fn parse<T: FromStr>(&self, s: &str) -> Result<T, ParseNumberError> {
+        let decimal = self.decimal(s)?;
+        let start = s.len() - s.trim_start_matches(['+', '-', '\u{2212}']).len();
+
+        decimal
+            .to_ascii()
+            .parse()
+            .map_err(|_| ParseNumberError::NotRepresentable {
+                offset: start,
+                type_name: any::type_name::<T>(),
+            })
+    }
+
+    fn decimal(&self, s: &str) -> Result<Decimal, ParseNumberError> {
+        if s.is_empty() {
+            return Err(ParseNumberError::Empty);
+        }
+
+        let mut chars = s.char_indices().peekable();
+
+        // `−` is the Unicode minus sign
+        let negative = match chars.peek() {
+            Some(&(_, '+')) => {
+                chars.next();
+                false
+            }
+            Some(&(_, '-')) | Some(&(_, '\u{2212}')) => {
+                chars.next();
+                true
+            }
+            _ => false,
+        };
+        let start = chars.peek().map_or(s.len(), |&(offset, _)| offset);
+
+        let mut digits = String::new();
+        let mut point = None;
+        let mut script = None;
+        let mut percent = false;
+
+        // Digits since the last group separator, and its offset
+        let mut group = 0;
+        let mut last_separator = None;
+
+        for (offset, c) in chars {
+            if percent {
+                return Err(ParseNumberError::InvalidCharacter {
+                    offset,
+                    character: c,
+                });
+            }
+
+            if let Some((zero, value)) = decimal_digit(c) {
+                if *script.get_or_insert(zero) != zero {
+                    return Err(ParseNumberError::MixedDigits { offset });
+                }
+                digits.push(char::from(b'0' + value));
+                group += 1;
+            } else if Some(c) == self.grouping {
+                let misplaced = point.is_some()
+                    || group == 0
+                    || (last_separator.is_some() && group != 3)
+                    || group > 3;
+                if misplaced {
+                    return Err(ParseNumberError::MisplacedGroupSeparator { offset });
+                }
+                last_separator = Some(offset);
+                group = 0;
+            } else if c == self.decimal {
+                if point.is_some() {
+                    return Err(ParseNumberError::MultipleDecimalSeparators { offset });
+                }
+                check_last_group(last_separator, group)?;
+                point = Some(digits.len());
+            } else if c == '%' {
+                percent = true;
+            } else {
+                return Err(ParseNumberError::InvalidCharacter {
+                    offset,
+                    character: c,
+                });
+            }
+        }
+
+        if digits.is_empty() {
+            return Err(ParseNumberError::NoDigits { offset: start });
+        }
+        if point.is_none() {
+            check_last_group(last_separator, group)?;
+        }
+
+        let decimal = Decimal {
+            negative,
+            point: point.unwrap_or(digits.len()),
+            digits,
+        };
+
+        Ok(if percent {
+            decimal.shift(false)
+        } else {
+            decimal
+        })
+    }
+
+    // As `Display` of `T` with the separators and digits, `parse` gives back
+    // the same `T`, `NaN` and infinities are left as they are
+    pub fn format<T: fmt::Display>(&self, value: T) -> String {
+        let s = value.to_string();
+
+        match Decimal::from_display(&s) {
+            Some(decimal) => self.write(&decimal, ""),
+            None => s,
+        }
+    }
+
+    // Times 100 with a `%`, as `format` otherwise
+    pub fn format_percent<T: fmt::Display>(&self, value: T) -> String {
+        let s = value.to_string();
+
+        match Decimal::from_display(&s) {
+            Some(decimal) => self.write(&decimal.shift(true), "%"),
+            None => s,
+        }
+    }
+
+    fn write(&self, decimal: &Decimal, suffix: &str) -> String {
+        let (integer, fraction) = decimal.parts();
+        let mut s = String::new();
+
+        if decimal.negative {
+            s.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i != 0 && (integer.len() - i) % 3 == 0 {
+                s.extend(self.grouping);
+            }
+            s.push(self.digit(digit));
+        }
+        if !fraction.is_empty() {
+            s.push(self.decimal);
+            s.extend(fraction.chars().map(|digit| self.digit(digit)));
+        }
+        s.push_str(suffix);
+
+        s
+    }
+
+    fn digit(&self, ascii: char) -> char {
+        let value = ascii as u32 - '0' as u32;
+
+        char::from_u32(self.zero as u32 + value).unwrap()
+    }
+}
+
+impl Default for NumberParser {
+    fn default() -> Self {
+        NumberParser::PLAIN
+    }
+}
+
+const fn is_separator(c: char) -> bool {
+    !matches!(c, '0'..='9' | '+' | '-' | '\u{2212}' | '%')
+}
+
+// The group before the decimal separator or the end, `1,23` is misplaced
+fn check_last_group(last_separator: Option<usize>, group: usize) -> Result<(), ParseNumberError> {
+    match last_separator {
+        Some(offset) if group != 3 => Err(ParseNumberError::MisplacedGroupSeparator { offset }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locales() {
+        assert_eq!(NumberParser::ENGLISH.parse("1,234.5"), Ok(1234.5));
+        assert_eq!(NumberParser::GERMAN.parse("1.234,5"), Ok(1234.5));
+        assert_eq!(NumberParser::FRENCH.parse("1 234,5"), Ok(1234.5));
+        assert_eq!(NumberParser::PLAIN.parse("1234.5"), Ok(1234.5));
+
+        // Grouping is optional
+        assert_eq!(NumberParser::ENGLISH.parse("1234567"), Ok(1_234_567));
+        assert_eq!(NumberParser::ENGLISH.parse("1,234,567"), Ok(1_234_567));
+        assert_eq!(NumberParser::ENGLISH.parse(".5"), Ok(0.5));
+        assert_eq!(NumberParser::ENGLISH.parse("5."), Ok(5.0));
+        // Only the separators of the locale
+        assert!(NumberParser::GERMAN.parse::<f64>("1,234.5").is_err());
+    }
+
+    #[test]
+    fn test_signs_and_percent() {
+        assert_eq!(NumberParser::ENGLISH.parse("+7"), Ok(7));
+        assert_eq!(NumberParser::ENGLISH.parse("-7"), Ok(-7));
+        assert_eq!(NumberParser::ENGLISH.parse("\u{2212}7"), Ok(-7));
+        assert_eq!(NumberParser::ENGLISH.parse("50%"), Ok(0.5));
+        assert_eq!(NumberParser::ENGLISH.parse("-12.5%"), Ok(-0.125));
+        assert_eq!(NumberParser::GERMAN.parse("1.250%"), Ok(12.5));
+        assert_eq!(NumberParser::ENGLISH.parse("0.07%"), Ok(0.0007));
+        // An exact integer
+        assert_eq!(NumberParser::ENGLISH.parse("300%"), Ok(3_u8));
+        assert_eq!(NumberParser::GERMAN.parse("2,000"), Ok(2_u8));
+    }
+
+    #[test]
+    fn test_unicode_digits() {
+        assert_eq!(NumberParser::ENGLISH.parse("١٢٣"), Ok(123));
+        assert_eq!(NumberParser::ENGLISH.parse("१,२३४.५"), Ok(1234.5));
+        assert_eq!(NumberParser::ENGLISH.parse("１２"), Ok(12));
+        assert_eq!(NumberParser::ENGLISH.parse("๔๒"), Ok(42));
+
+        for &zero in DIGIT_ZEROS.iter() {
+            for value in 0..10 {
+                let c = char::from_u32(zero as u32 + value).unwrap();
+                assert!(c.is_numeric(), "{:?}", c);
+                assert_eq!(decimal_digit(c), Some((zero, value as u8)));
+            }
+        }
+        assert_eq!(decimal_digit('a'), None);
+        assert_eq!(decimal_digit('½'), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        fn error(parser: NumberParser, s: &str) -> ParseNumberError {
+            parser.parse::<f64>(s).unwrap_err()
+        }
+
+        let en = NumberParser::ENGLISH;
+        assert_eq!(error(en, ""), ParseNumberError::Empty);
+        assert_eq!(error(en, "-"), ParseNumberError::NoDigits { offset: 1 });
+        assert_eq!(error(en, "-%"), ParseNumberError::NoDigits { offset: 1 });
+        assert_eq!(
+            error(en, "12a"),
+            ParseNumberError::InvalidCharacter {
+                offset: 2,
+                character: 'a'
+            }
+        );
+        assert_eq!(
+            error(en, " 1"),
+            ParseNumberError::InvalidCharacter {
+                offset: 0,
+                character: ' '
+            }
+        );
+        assert_eq!(
+            error(en, "5%%"),
+            ParseNumberError::InvalidCharacter {
+                offset: 2,
+                character: '%'
+            }
+        );
+        assert_eq!(
+            error(en, "12,34"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 2 }
+        );
+        assert_eq!(
+            error(en, "1234,567"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 4 }
+        );
+        assert_eq!(
+            error(en, "1,234,56.7"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 5 }
+        );
+        assert_eq!(
+            error(en, ",123"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 0 }
+        );
+        assert_eq!(
+            error(en, "1,,234"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 2 }
+        );
+        assert_eq!(
+            error(en, "1.234,5"),
+            ParseNumberError::MisplacedGroupSeparator { offset: 5 }
+        );
+        assert_eq!(
+            error(en, "1.2.3"),
+            ParseNumberError::MultipleDecimalSeparators { offset: 3 }
+        );
+        // `١` is 2 bytes
+        assert_eq!(error(en, "١2"), ParseNumberError::MixedDigits { offset: 2 });
+        assert_eq!(
+            en.parse::<u8>("-1,000").unwrap_err(),
+            ParseNumberError::NotRepresentable {
+                offset: 1,
+                type_name: "u8"
+            }
+        );
+        assert!(en.parse::<i32>("1.5").is_err());
+
+        assert_eq!(
+            error(en, "12,34").to_string(),
+            "byte 2: group separators must be between groups of 3 digits"
+        );
+        assert_eq!(
+            en.parse::<u8>("256").unwrap_err().to_string(),
+            "byte 0: not a valid `u8`"
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(NumberParser::ENGLISH.format(1234.5), "1,234.5");
+        assert_eq!(NumberParser::GERMAN.format(1234.5), "1.234,5");
+        assert_eq!(NumberParser::FRENCH.format(-1234567), "-1 234 567");
+        assert_eq!(NumberParser::PLAIN.format(1234567), "1234567");
+        assert_eq!(NumberParser::ENGLISH.format(123), "123");
+        assert_eq!(NumberParser::ENGLISH.format(0.25), "0.25");
+        assert_eq!(NumberParser::ENGLISH.format_percent(0.125), "12.5%");
+        assert_eq!(NumberParser::ENGLISH.format_percent(-30), "-3,000%");
+        assert_eq!(NumberParser::ENGLISH.format_percent(0.0007), "0.07%");
+        assert_eq!(
+            NumberParser::ENGLISH.with_digits('\u{0966}').format(1234.5),
+            "१,२३४.५"
+        );
+        assert_eq!(NumberParser::ENGLISH.format(f64::NAN), "NaN");
+        assert_eq!(NumberParser::ENGLISH.format(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let parsers = [
+            NumberParser::PLAIN,
+            NumberParser::ENGLISH,
+            NumberParser::GERMAN,
+            NumberParser::FRENCH.with_digits('\u{0660}'),
+            NumberParser::new(Some('\''), '.').with_digits('\u{FF10}'),
+        ];
+        let floats = [
+            0.0,
+            -0.0,
+            0.1,
+            1.5,
+            -1234.5,
+            1e-9,
+            123456789.125,
+            1e21,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+        ];
+        let ints = [0, 1, -999, 1000, i64::MAX, i64::MIN];
+
+        for parser in &parsers {
+            for &x in &floats {
+                let parsed: f64 = parser.parse(&parser.format(x)).unwrap();
+                assert_eq!(parsed.to_bits(), x.to_bits(), "{}", parser.format(x));
+
+                let parsed: f64 = parser.parse(&parser.format_percent(x)).unwrap();
+                assert_eq!(
+                    parsed.to_bits(),
+                    x.to_bits(),
+                    "{}",
+                    parser.format_percent(x)
+                );
+            }
+            for &n in &ints {
+                assert_eq!(parser.parse(&parser.format(n)), Ok(n));
+                assert_eq!(parser.parse(&parser.format_percent(n)), Ok(n));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the separators must differ")]
+    fn test_same_separators() {
+        NumberParser::new(Some(','), ',');
+    }
+}
@@ -6,6 +6,11 @@
 //    6.2. TryFrom and TryInto
 //    6.3. To and from Strings
 
+mod locale;
+mod number;
+mod refined;
+mod shapes;
+
 fn main() {
     // https://doc.rust-lang.org/rust-by-example/conversion.html
     println!("\n--- 6. Conversion ---");
@@ -20,18 +25,11 @@ fn main() {
         // NOTE: Included to Rust 2018 prelude
         // use std::convert::From;
 
-        #[derive(Debug)]
-        struct Number {
-            value: i32,
-        }
+        // NOTE: `Number` moved to `number.rs`, for any integer type, it's
+        //       `Number<i32>` by default
+        use number::Number;
 
-        impl From<i32> for Number {
-            fn from(item: i32) -> Self {
-                Number { value: item }
-            }
-        }
-
-        let num = Number::from(30);
+        let num: Number = Number::from(30);
         println!("My number is {:?}", num);
 
         let int = 5;
@@ -39,6 +37,31 @@ fn main() {
         //       NOTE: Compile error
         let num: Number = int.into();
         println!("My number is {:?}", num);
+
+        // NOTE: lossless conversions are `From`, the others `TryFrom`
+        {
+            use std::convert::TryFrom;
+
+            let wide: Number<i64> = 7u8.into();
+            println!("u8 into Number<i64>: {}", wide);
+            println!("300 into Number<u8>: {:?}", Number::<u8>::try_from(300));
+            println!("-1 into Number<u32>: {:?}", Number::<u32>::try_from(-1));
+            println!(
+                "u64::MAX into Number<u128>: {}",
+                Number::<u128>::from(u64::MAX)
+            );
+
+            println!(
+                "\"ff\" in hex: {:?}",
+                Number::<u8>::from_str_radix("ff", 16)
+            );
+            println!(
+                "\"777\" in octal: {:?}",
+                Number::<u8>::from_str_radix("777", 8)
+            );
+            let text: String = num.into();
+            println!("as a `String`: {:?}", text);
+        }
     }
 
     // https://doc.rust-lang.org/rust-by-example/conversion/try_from_try_into.html
@@ -71,24 +94,69 @@ fn main() {
         assert_eq!(result, Ok(EvenNumber(8)));
         let result: Result<EvenNumber, ()> = 5i32.try_into();
         assert_eq!(result, Err(()));
-    }
 
-    // https://doc.rust-lang.org/rust-by-example/conversion/string.html
-    println!("\n--- 6.3. To and from Strings ---");
-    {
-        use std::fmt;
+        // NOTE: `refined.rs` declares such types from a predicate, with
+        //       errors that say which one failed
+        {
+            use refined::{Even, InRange, NonZero, Positive};
 
-        struct Circle {
-            radius: i32,
-        }
+            println!("{:?}", Even::try_from(8));
+            if let Err(e) = Even::try_from(5) {
+                println!("not an `Even`: {}", e);
+            }
+            if let Err(e) = "7".parse::<Even>() {
+                println!("not an `Even`: {}", e);
+            }
+
+            let percent: Result<InRange<0, 100>, _> = 120.try_into();
+            match percent {
+                Ok(percent) => println!("{}%", percent),
+                Err(e) => println!("not a percentage: {}", e),
+            }
+
+            match "-2.5".parse::<Positive<f64>>() {
+                Ok(value) => println!("positive: {}", value),
+                Err(e) => println!("not positive: {}", e),
+            }
+            match "zero".parse::<NonZero>() {
+                Ok(value) => println!("non-zero: {}", value),
+                Err(e) => println!("not non-zero: {}", e),
+            }
+
+            // Operators give back an `i32`, `try_*` re-validate
+            let (a, b): (Even, Even) = (Even::try_from(8).unwrap(), Even::try_from(-2).unwrap());
+            println!("{:?} + {:?} = {}", a, b, a + b);
+            println!("{:?} * {:?} = {:?}", a, b, a.try_mul(b));
+            println!("{:?} - {:?} = {:?}", b, a, b.try_sub(a));
+            // `Deref` to the `i32`
+            println!("|{:?}| = {}", b, b.abs());
+            println!("{:?} back to an `i32`: {}", a, a.into_inner());
 
-        impl fmt::Display for Circle {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "Circle of radius {}", self.radius)
+            let small = InRange::<1, 10>::try_from(6).unwrap();
+            match small.try_add(small) {
+                Ok(sum) => println!("{} + {} = {}", small, small, sum),
+                Err(e) => println!("{} + {} is out of range: {}", small, small, e),
+            }
+
+            // `try_*` also catch overflow, `-` would panic here
+            let (one, two) = (
+                Positive::<u8>::try_from(1).unwrap(),
+                Positive::<u8>::try_from(2).unwrap(),
+            );
+            match one.try_sub(two) {
+                Ok(difference) => println!("{} - {} = {}", one, two, difference),
+                Err(e) => println!("{} - {}: {}", one, two, e),
             }
         }
+    }
 
-        let circle = Circle { radius: 6 };
+    // https://doc.rust-lang.org/rust-by-example/conversion/string.html
+    println!("\n--- 6.3. To and from Strings ---");
+    {
+        // NOTE: `Circle` moved to `shapes.rs`, its `Display` is the same
+        use shapes::Circle;
+
+        let circle = Circle::new(6).unwrap();
         println!("{}", circle.to_string());
 
         let parsed: i32 = "5".parse().unwrap();
@@ -98,5 +166,105 @@ fn main() {
 
         let sum = parsed + turbo_parsed;
         println!("Sum: {:?}", sum);
+
+        // NOTE: `parse` only reads plain ASCII numbers, `locale.rs` reads
+        //       them as people write them
+        {
+            use locale::NumberParser;
+
+            let locales = [
+                ("English", NumberParser::ENGLISH),
+                ("German", NumberParser::GERMAN),
+                ("French", NumberParser::FRENCH),
+            ];
+            for (name, parser) in locales.iter() {
+                let formatted = parser.format(-1234567.25);
+                let parsed: f64 = parser.parse(&formatted).unwrap();
+                println!("{}: {} is {}", name, formatted, parsed);
+            }
+
+            let english = NumberParser::ENGLISH;
+            for text in ["12.5%", "١٬٢٣٤", "१२३", "12,34", "1.2.3", "300"] {
+                match english.parse::<u8>(text) {
+                    Ok(value) => println!("{:?} as a `u8`: {}", text, value),
+                    Err(e) => println!("{:?} as a `u8`: {}", text, e),
+                }
+            }
+            let ratio: f64 = english.parse("12.5%").unwrap();
+            println!("12.5% is {}, or {}", ratio, english.format_percent(ratio));
+            println!(
+                "in Devanagari: {}",
+                english.with_digits('\u{0966}').format(sum)
+            );
+        }
+
+        // NOTE: and back, the text of a shape parses to the same shape
+        {
+            use shapes::{Point, Polygon, Scene, Shape, Square};
+
+            let parsed: Circle = circle.to_string().parse().unwrap();
+            println!("{} parses back: {}", circle, parsed == circle);
+
+            let moved = Circle::new(2).unwrap().at(Point::new(5, -1));
+            let square = Square::new(4).unwrap().at(Point::new(-3, 0));
+            println!("{} and {}", moved, square);
+            println!(
+                "radius {} around {}, side {} from {}",
+                moved.radius(),
+                moved.center(),
+                square.side(),
+                square.corner()
+            );
+
+            // Negative sizes and polygons of less than 3 points can't be
+            // built, nor written
+            println!("a circle of radius -6: {:?}", Circle::new(-6));
+            let line = vec![Point::new(0, 0), Point::new(4, 0)];
+            println!("a polygon of 2 points: {:?}", Polygon::new(line));
+            let corners = vec![Point::new(0, 0), Point::new(4, 0), Point::new(0, 3)];
+            let triangle = Polygon::new(corners).unwrap();
+            println!("{}, {} points", triangle, triangle.points().len());
+
+            for text in [
+                "Square of side 4 at (-3, 0)",
+                "Circle of radius -6",
+                "Hexagon of side 2",
+            ] {
+                match text.parse::<Shape>() {
+                    Ok(shape) => println!("{:?}, area {}", shape, shape.area()),
+                    Err(e) => println!("{:?}: {}", text, e),
+                }
+            }
+
+            let scene = "\
+# One shape per line
+Circle of radius 2 at (5, -1)
+Square of side 4 at (-3, 0)
+Polygon of points (0, 0), (4, 0), (0, 3)
+";
+            match scene.parse::<Scene>() {
+                Ok(scene) => {
+                    println!(
+                        "{} shapes, total area {:.2}",
+                        scene.shapes.len(),
+                        scene.total_area()
+                    );
+                    if let Some(bounds) = scene.bounding_box() {
+                        println!(
+                            "bounding box {}, {}x{}",
+                            bounds,
+                            bounds.width(),
+                            bounds.height()
+                        );
+                    }
+                }
+                Err(e) => println!("invalid scene: {}", e),
+            }
+
+            let broken = "Circle of radius 1\nPolygon of points (0, 0), (1 1)";
+            if let Err(e) = broken.parse::<Scene>() {
+                println!("invalid scene: {}", e);
+            }
+        }
     }
 }
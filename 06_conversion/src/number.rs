@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the `Number` of 6.1 for any integer type, `Number<T>` converts from
+//       the other integers as `T` does:
+//         Number::<i32>::from(7u8)       // lossless, always works
+//         Number::<u8>::try_from(300)    // Err(TryFromIntError(()))
+//         Number::<u32>::try_from(-1)    // Err(..), signs differ
+//       `usize` and `isize` get what fits on every platform, e.g. only
+//       `TryFrom<u32>`
+//       And from and to text:
+//         Number::<u8>::from_str_radix("ff", 16)    // Ok(Number { value: 255 })
+//         let s: String = Number::<i32>::from(7).into();
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::{ParseIntError, TryFromIntError};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Number<T = i32> {
+    pub value: T,
+}
+
+// The integer types, for `from_str_radix`
+pub trait Integer: Sized {
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+                    <$t>::from_str_radix(s, radix)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// Per target type, the lossless sources, which `std` has `From` for, and
+// the others, each type appears once per line
+macro_rules! conversions {
+    ($($t:ty: from $($from:ty),*; try_from $($try_from:ty),*;)*) => {
+        $(
+            $(
+                impl From<$from> for Number<$t> {
+                    fn from(value: $from) -> Self {
+                        Number {
+                            value: <$t>::from(value),
+                        }
+                    }
+                }
+            )*
+            $(
+                impl TryFrom<$try_from> for Number<$t> {
+                    type Error = TryFromIntError;
+
+                    fn try_from(value: $try_from) -> Result<Self, Self::Error> {
+                        <$t>::try_from(value).map(|value| Number { value })
+                    }
+                }
+            )*
+        )*
+    };
+}
+
+conversions! {
+    u8: from u8;
+        try_from u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize;
+    u16: from u8, u16;
+        try_from u32, u64, u128, usize, i8, i16, i32, i64, i128, isize;
+    u32: from u8, u16, u32;
+        try_from u64, u128, usize, i8, i16, i32, i64, i128, isize;
+    u64: from u8, u16, u32, u64;
+        try_from u128, usize, i8, i16, i32, i64, i128, isize;
+    u128: from u8, u16, u32, u64, u128;
+        try_from usize, i8, i16, i32, i64, i128, isize;
+    usize: from u8, u16, usize;
+        try_from u32, u64, u128, i8, i16, i32, i64, i128, isize;
+    i8: from i8;
+        try_from u8, u16, u32, u64, u128, usize, i16, i32, i64, i128, isize;
+    i16: from u8, i8, i16;
+        try_from u16, u32, u64, u128, usize, i32, i64, i128, isize;
+    i32: from u8, u16, i8, i16, i32;
+        try_from u32, u64, u128, usize, i64, i128, isize;
+    i64: from u8, u16, u32, i8, i16, i32, i64;
+        try_from u64, u128, usize, i128, isize;
+    i128: from u8, u16, u32, u64, i8, i16, i32, i64, i128;
+        try_from u128, usize, isize;
+    isize: from u8, i8, i16, isize;
+        try_from u16, u32, u64, u128, usize, i32, i64, i128;
+}
+
+impl<T: Integer> Number<T> {
+    // As `i32::from_str_radix`, e.g. `Number::<u8>::from_str_radix("ff", 16)`
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+        T::from_str_radix(s, radix).map(|value| Number { value })
+    }
+}
+
+impl<T: FromStr> FromStr for Number<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(|value| Number { value })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Number<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+// Gives `Into<String>` for `Number`
+impl<T: fmt::Display> From<Number<T>> for String {
+    fn from(number: Number<T>) -> String {
+        number.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    // Each pair has one `TryFrom`: the `std` one through `From` with
+    // `Infallible` errors, or one of `conversions!`, so these only compile
+    // for the right pairs, and only if `std` agrees
+    fn infallible<S, T: From<S>>()
+    where
+        Number<T>: From<S> + TryFrom<S, Error = Infallible>,
+    {
+    }
+
+    fn fallible<S, T: TryFrom<S, Error = TryFromIntError>>()
+    where
+        Number<T>: TryFrom<S, Error = TryFromIntError>,
+    {
+    }
+
+    macro_rules! matrix {
+        ($($t:ty: from $($from:ty),*; try_from $($try_from:ty),*;)*) => {
+            $(
+                $(infallible::<$from, $t>();)*
+                $(fallible::<$try_from, $t>();)*
+            )*
+        };
+    }
+
+    #[test]
+    fn test_conversion_matrix() {
+        matrix! {
+            u8: from u8;
+                try_from u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize;
+            u16: from u8, u16;
+                try_from u32, u64, u128, usize, i8, i16, i32, i64, i128, isize;
+            u32: from u8, u16, u32;
+                try_from u64, u128, usize, i8, i16, i32, i64, i128, isize;
+            u64: from u8, u16, u32, u64;
+                try_from u128, usize, i8, i16, i32, i64, i128, isize;
+            u128: from u8, u16, u32, u64, u128;
+                try_from usize, i8, i16, i32, i64, i128, isize;
+            usize: from u8, u16, usize;
+                try_from u32, u64, u128, i8, i16, i32, i64, i128, isize;
+            i8: from i8;
+                try_from u8, u16, u32, u64, u128, usize, i16, i32, i64, i128, isize;
+            i16: from u8, i8, i16;
+                try_from u16, u32, u64, u128, usize, i32, i64, i128, isize;
+            i32: from u8, u16, i8, i16, i32;
+                try_from u32, u64, u128, usize, i64, i128, isize;
+            i64: from u8, u16, u32, i8, i16, i32, i64;
+                try_from u64, u128, usize, i128, isize;
+            i128: from u8, u16, u32, u64, i8, i16, i32, i64, i128;
+                try_from u128, usize, isize;
+            isize: from u8, i8, i16, isize;
+                try_from u16, u32, u64, u128, usize, i32, i64, i128;
+        }
+    }
+
+    // The 6.1 examples
+    #[test]
+    fn test_section_number() {
+        let num = Number::from(30);
+        assert_eq!(num, Number { value: 30 });
+
+        let num: Number = 5.into();
+        assert_eq!(num.value, 5);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Number::<i32>::from(u16::MAX).value, 65535);
+        assert_eq!(Number::<i64>::from(i8::MIN).value, -128);
+        assert_eq!(Number::<u8>::try_from(255_i32), Ok(Number { value: 255 }));
+        assert!(Number::<u8>::try_from(256_i32).is_err());
+        assert!(Number::<u32>::try_from(-1_i8).is_err());
+        assert!(Number::<i8>::try_from(128_u8).is_err());
+        assert_eq!(
+            Number::<usize>::try_from(u32::MAX),
+            Ok(Number {
+                value: u32::MAX as usize
+            })
+        );
+        assert!(Number::<u128>::try_from(i128::MIN).is_err());
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            Number::<u8>::from_str_radix("ff", 16),
+            Ok(Number { value: 255 })
+        );
+        assert_eq!(
+            Number::<i32>::from_str_radix("-101", 2),
+            Ok(Number { value: -5 })
+        );
+        assert!(Number::<u8>::from_str_radix("100", 16).is_err());
+        assert_eq!("-7".parse::<Number>(), Ok(Number { value: -7 }));
+
+        let s: String = Number::<u64>::from(42_u32).into();
+        assert_eq!(s, "42");
+        assert_eq!(format!("{:>4}", Number::<i8>::from(-3)), "  -3");
+    }
+}
@@ -0,0 +1,499 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: `EvenNumber` of 6.2 for any predicate: a `Refined<T, P>` holds a `T`
+//       for which the `Predicate` `P` is true, it's checked once, on
+//       creation:
+//         Even::try_from(8)              // Ok(Even(8))
+//         "5".parse::<Even>()            // Err(5 must be even)
+//         InRange::<1, 10>::try_from(11) // Err(11 must be in 1..=10)
+//       The operators give back the raw type, `try_add` and friends check
+//       for overflow and the result again, with `eight` and `two` as `Even`s:
+//         eight + two                    // 10
+//         eight.try_add(two)             // Ok(Even(10))
+//       New predicates only need a `Predicate` impl
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Deref, Mul, Sub};
+use std::str::FromStr;
+
+pub trait Predicate<T> {
+    // For `Debug`, e.g. `Even(8)`
+    const NAME: &'static str;
+
+    fn test(value: &T) -> bool;
+
+    // What the values must be, e.g. `even` or `in 1..=10`
+    fn constraint() -> String;
+}
+
+pub struct Refined<T, P> {
+    value: T,
+    predicate: PhantomData<P>,
+}
+
+pub struct IsEven;
+pub struct IsPositive;
+pub struct IsNonZero;
+pub struct Within<const MIN: i64, const MAX: i64>;
+
+pub type Even<T = i32> = Refined<T, IsEven>;
+pub type Positive<T = i32> = Refined<T, IsPositive>;
+pub type NonZero<T = i32> = Refined<T, IsNonZero>;
+pub type InRange<const MIN: i64, const MAX: i64, T = i32> = Refined<T, Within<MIN, MAX>>;
+
+macro_rules! impl_predicates {
+    (ints: $($int:ty),*; floats: $($float:ty),*) => {
+        $(
+            impl Predicate<$int> for IsEven {
+                const NAME: &'static str = "Even";
+
+                fn test(value: &$int) -> bool {
+                    value % 2 == 0
+                }
+
+                fn constraint() -> String {
+                    "even".to_owned()
+                }
+            }
+
+            impl<const MIN: i64, const MAX: i64> Predicate<$int> for Within<MIN, MAX> {
+                const NAME: &'static str = "InRange";
+
+                // `i128` holds every value and bound
+                fn test(value: &$int) -> bool {
+                    (MIN as i128..=MAX as i128).contains(&(*value as i128))
+                }
+
+                fn constraint() -> String {
+                    format!("in {}..={}", MIN, MAX)
+                }
+            }
+        )*
+        $(
+            impl Predicate<$int> for IsPositive {
+                const NAME: &'static str = "Positive";
+
+                fn test(value: &$int) -> bool {
+                    *value > 0
+                }
+
+                fn constraint() -> String {
+                    "positive".to_owned()
+                }
+            }
+
+            impl Predicate<$int> for IsNonZero {
+                const NAME: &'static str = "NonZero";
+
+                fn test(value: &$int) -> bool {
+                    *value != 0
+                }
+
+                fn constraint() -> String {
+                    "non-zero".to_owned()
+                }
+            }
+        )*
+        $(
+            // NaN is neither
+            impl Predicate<$float> for IsPositive {
+                const NAME: &'static str = "Positive";
+
+                fn test(value: &$float) -> bool {
+                    *value > 0.0
+                }
+
+                fn constraint() -> String {
+                    "positive".to_owned()
+                }
+            }
+
+            impl Predicate<$float> for IsNonZero {
+                const NAME: &'static str = "NonZero";
+
+                fn test(value: &$float) -> bool {
+                    *value != 0.0 && !value.is_nan()
+                }
+
+                fn constraint() -> String {
+                    "non-zero".to_owned()
+                }
+            }
+        )*
+    };
+}
+
+impl_predicates!(
+    ints: i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+    floats: f32, f64
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefinementError<T> {
+    pub value: T,
+    // As `Predicate::constraint`
+    pub constraint: String,
+}
+
+impl<T: fmt::Display> fmt::Display for RefinementError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} must be {}", self.value, self.constraint)
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> error::Error for RefinementError<T> {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseRefinedError<T, E> {
+    // Not a `T` at all
+    Parse(E),
+    Refinement(RefinementError<T>),
+}
+
+impl<T: fmt::Display, E: fmt::Display> fmt::Display for ParseRefinedError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRefinedError::Parse(e) => write!(f, "invalid number: {}", e),
+            ParseRefinedError::Refinement(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<T, E> error::Error for ParseRefinedError<T, E>
+where
+    T: fmt::Debug + fmt::Display,
+    E: fmt::Debug + fmt::Display,
+{
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticError<T> {
+    // The result doesn't fit in a `T`, e.g. `Positive::<u8>(1) - Positive(2)`
+    Overflow,
+    Refinement(RefinementError<T>),
+}
+
+impl<T: fmt::Display> fmt::Display for ArithmeticError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArithmeticError::Overflow => write!(f, "arithmetic overflow"),
+            ArithmeticError::Refinement(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> error::Error for ArithmeticError<T> {}
+
+impl<T, P: Predicate<T>> Refined<T, P> {
+    pub fn new(value: T) -> Result<Self, RefinementError<T>> {
+        if P::test(&value) {
+            Ok(Refined {
+                value,
+                predicate: PhantomData,
+            })
+        } else {
+            Err(RefinementError {
+                value,
+                constraint: P::constraint(),
+            })
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+// For each type, a generic `TryFrom<T>` would overlap with the `std` one
+macro_rules! impl_try_from {
+    ($($t:ty),*) => {
+        $(
+            impl<P: Predicate<$t>> TryFrom<$t> for Refined<$t, P> {
+                type Error = RefinementError<$t>;
+
+                fn try_from(value: $t) -> Result<Self, Self::Error> {
+                    Refined::new(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: FromStr, P: Predicate<T>> FromStr for Refined<T, P> {
+    type Err = ParseRefinedError<T, T::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.parse().map_err(ParseRefinedError::Parse)?;
+
+        Refined::new(value).map_err(ParseRefinedError::Refinement)
+    }
+}
+
+impl<T, P> Deref for Refined<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+// The `checked_*` methods of the primitives, for `try_add` and friends
+// NOTE: floats don't overflow, they go to infinity, which the predicates see
+pub trait Checked: Sized {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked {
+    (ints: $($int:ty),*; floats: $($float:ty),*) => {
+        $(
+            impl Checked for $int {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, rhs)
+                }
+            }
+        )*
+        $(
+            impl Checked for $float {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    Some(self + rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    Some(self - rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    Some(self * rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(
+    ints: i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+    floats: f32, f64
+);
+
+// The result may not hold, e.g. `Positive(1) - Positive(2)`, so the
+// operators give back a `T`
+macro_rules! impl_operator {
+    ($($op:ident, $method:ident, $checked_method:ident, $checked:ident;)*) => {
+        $(
+            impl<T: $op<Output = T>, P> $op for Refined<T, P> {
+                type Output = T;
+
+                fn $method(self, rhs: Self) -> T {
+                    self.value.$method(rhs.value)
+                }
+            }
+
+            impl<T: Checked, P: Predicate<T>> Refined<T, P> {
+                pub fn $checked(self, rhs: Self) -> Result<Self, ArithmeticError<T>> {
+                    let value = Checked::$checked_method(self.value, rhs.value)
+                        .ok_or(ArithmeticError::Overflow)?;
+
+                    Refined::new(value).map_err(ArithmeticError::Refinement)
+                }
+            }
+        )*
+    };
+}
+
+impl_operator! {
+    Add, add, checked_add, try_add;
+    Sub, sub, checked_sub, try_sub;
+    Mul, mul, checked_mul, try_mul;
+}
+
+// By hand, `derive` would require the same of `P`
+
+impl<T: Clone, P> Clone for Refined<T, P> {
+    fn clone(&self) -> Self {
+        Refined {
+            value: self.value.clone(),
+            predicate: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, P> Copy for Refined<T, P> {}
+
+impl<T: PartialEq, P> PartialEq for Refined<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: PartialOrd, P> PartialOrd for Refined<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: fmt::Debug, P: Predicate<T>> fmt::Debug for Refined<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple(P::NAME).field(&self.value).finish()
+    }
+}
+
+impl<T: fmt::Display, P> fmt::Display for Refined<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn even(value: i32) -> Even {
+        Even::try_from(value).unwrap()
+    }
+
+    // The 6.2 `EvenNumber` assertions
+    #[test]
+    fn test_even_number() {
+        assert_eq!(*Even::try_from(8).unwrap(), 8);
+        assert_eq!(
+            Even::try_from(5),
+            Err(RefinementError {
+                value: 5,
+                constraint: "even".to_owned()
+            })
+        );
+        assert!(Even::<u8>::try_from(0).is_ok());
+        assert!(Even::<i64>::try_from(-3).is_err());
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert!(Positive::try_from(1).is_ok());
+        assert!(Positive::try_from(0).is_err());
+        assert!(Positive::<f64>::try_from(0.5).is_ok());
+        assert!(Positive::<f64>::try_from(f64::NAN).is_err());
+        assert!(NonZero::<u8>::try_from(0).is_err());
+        assert!(NonZero::<f32>::try_from(-0.0).is_err());
+        assert!(NonZero::try_from(-7).is_ok());
+
+        assert!(InRange::<1, 10>::try_from(1).is_ok());
+        assert!(InRange::<1, 10>::try_from(10).is_ok());
+        assert!(InRange::<1, 10>::try_from(0).is_err());
+        assert!(InRange::<-5, 5, i8>::try_from(-5).is_ok());
+        assert!(InRange::<0, { i64::MAX }, u64>::try_from(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Even::<i32>::try_from(5).unwrap_err().to_string(),
+            "5 must be even"
+        );
+        assert_eq!(
+            InRange::<1, 10>::try_from(11).unwrap_err().to_string(),
+            "11 must be in 1..=10"
+        );
+        assert_eq!(
+            Positive::<i32>::try_from(-3).unwrap_err().to_string(),
+            "-3 must be positive"
+        );
+        assert_eq!(
+            NonZero::<i32>::try_from(0).unwrap_err().to_string(),
+            "0 must be non-zero"
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("8".parse::<Even>(), Ok(even(8)));
+        assert_eq!(
+            "5".parse::<Even>().unwrap_err().to_string(),
+            "5 must be even"
+        );
+        assert!(matches!(
+            "five".parse::<Even>(),
+            Err(ParseRefinedError::Parse(_))
+        ));
+        assert_eq!(
+            "300".parse::<Positive<u8>>().unwrap_err().to_string(),
+            "invalid number: number too large to fit in target type"
+        );
+        assert_eq!(*"2.5".parse::<Positive<f64>>().unwrap(), 2.5);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(even(8) + even(2), 10);
+        assert_eq!(even(8).try_add(even(2)), Ok(even(10)));
+        assert_eq!(even(8) * even(-2), -16);
+
+        let one: Positive = Positive::try_from(1).unwrap();
+        let two: Positive = Positive::try_from(2).unwrap();
+        assert_eq!(one - two, -1);
+        assert_eq!(
+            one.try_sub(two).unwrap_err().to_string(),
+            "-1 must be positive"
+        );
+
+        let nine = InRange::<1, 10>::try_from(9).unwrap();
+        assert!(matches!(
+            nine.try_mul(nine),
+            Err(ArithmeticError::Refinement(_))
+        ));
+        assert_eq!(nine.into_inner(), 9);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let one = Positive::<u8>::try_from(1).unwrap();
+        let two = Positive::<u8>::try_from(2).unwrap();
+        assert_eq!(one.try_sub(two), Err(ArithmeticError::Overflow));
+        assert_eq!(
+            one.try_sub(two).unwrap_err().to_string(),
+            "arithmetic overflow"
+        );
+
+        let big = Positive::<u8>::try_from(200).unwrap();
+        assert_eq!(big.try_add(big), Err(ArithmeticError::Overflow));
+        assert_eq!(big.try_mul(two), Err(ArithmeticError::Overflow));
+        assert_eq!(*big.try_add(one).unwrap(), 201);
+
+        let min = NonZero::<i64>::try_from(i64::MIN).unwrap();
+        assert_eq!(min.try_mul(min), Err(ArithmeticError::Overflow));
+
+        // Floats go to infinity instead, which is still positive
+        let huge = Positive::<f64>::try_from(f64::MAX).unwrap();
+        assert_eq!(*huge.try_add(huge).unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_traits() {
+        assert_eq!(format!("{:?}", even(8)), "Even(8)");
+        assert_eq!(
+            format!("{:?}", InRange::<1, 10>::try_from(3).unwrap()),
+            "InRange(3)"
+        );
+        assert_eq!(format!("{:>3}", even(8)), "  8");
+        assert!(even(2) < even(4));
+
+        // `Deref` gives the methods of the inner value
+        assert_eq!(even(-4).abs(), 4);
+        assert_eq!(even(6).pow(2), 36);
+    }
+}
@@ -0,0 +1,787 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// NOTE: the `Circle` of 6.3 and its friends, written and read as text:
+//         Circle of radius 6
+//         Circle of radius 2 at (5, -1)
+//         Square of side 4 at (-3, 0)
+//         Polygon of points (0, 0), (4, 0), (0, 3)
+//       Circles are centered on their point, squares have their lower left
+//       corner on it, both are at (0, 0) without one
+//       Sizes are never negative and polygons have at least 3 points, the
+//       constructors give `None` otherwise
+//       A scene is a shape per line, blank lines and `#` comments are
+//       skipped, errors have their line and column:
+//         line 2, column 18: expected a number
+
+use std::error;
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle {
+    radius: i32,
+    center: Point,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Square {
+    side: i32,
+    // The lower left one
+    corner: Point,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    // At least 3, in order around the polygon
+    points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle(Circle),
+    Square(Square),
+    Polygon(Polygon),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseShapeErrorKind {
+    // e.g. "`radius`" or "a number"
+    Expected(&'static str),
+    UnknownShape(String),
+    NumberTooLarge,
+    NegativeSize,
+    TooFewPoints(usize),
+    TrailingInput,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseShapeError {
+    // In characters, starting at 1
+    pub column: usize,
+    pub kind: ParseShapeErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseSceneError {
+    // Starting at 1
+    pub line: usize,
+    pub error: ParseShapeError,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+}
+
+impl Circle {
+    // `None` for a negative radius
+    pub fn new(radius: i32) -> Option<Circle> {
+        if radius < 0 {
+            return None;
+        }
+
+        Some(Circle {
+            radius,
+            center: Point::default(),
+        })
+    }
+
+    pub fn at(self, center: Point) -> Circle {
+        Circle { center, ..self }
+    }
+
+    pub fn radius(&self) -> i32 {
+        self.radius
+    }
+
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    pub fn area(&self) -> f64 {
+        PI * (self.radius as f64).powi(2)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let Point { x, y } = self.center;
+        let r = self.radius;
+
+        BoundingBox {
+            min: Point::new(x.saturating_sub(r), y.saturating_sub(r)),
+            max: Point::new(x.saturating_add(r), y.saturating_add(r)),
+        }
+    }
+}
+
+impl Square {
+    // `None` for a negative side
+    pub fn new(side: i32) -> Option<Square> {
+        if side < 0 {
+            return None;
+        }
+
+        Some(Square {
+            side,
+            corner: Point::default(),
+        })
+    }
+
+    pub fn at(self, corner: Point) -> Square {
+        Square { corner, ..self }
+    }
+
+    pub fn side(&self) -> i32 {
+        self.side
+    }
+
+    pub fn corner(&self) -> Point {
+        self.corner
+    }
+
+    pub fn area(&self) -> f64 {
+        (self.side as f64).powi(2)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let Point { x, y } = self.corner;
+
+        BoundingBox {
+            min: self.corner,
+            max: Point::new(x.saturating_add(self.side), y.saturating_add(self.side)),
+        }
+    }
+}
+
+impl Polygon {
+    // `None` for less than 3 points
+    pub fn new(points: Vec<Point>) -> Option<Polygon> {
+        if points.len() < 3 {
+            return None;
+        }
+
+        Some(Polygon { points })
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    // The shoelace formula, for polygons that don't cross themselves,
+    // summed in `i128`: a single term of extreme `i32` corners is near 2^63
+    pub fn area(&self) -> f64 {
+        let twice: i128 = self
+            .points
+            .iter()
+            .zip(self.points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128)
+            .sum();
+
+        twice.abs() as f64 / 2.0
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        // `new` and the parser make sure there is a first point
+        let first = BoundingBox {
+            min: self.points[0],
+            max: self.points[0],
+        };
+
+        self.points.iter().fold(first, |bounds, &point| {
+            bounds.union(BoundingBox {
+                min: point,
+                max: point,
+            })
+        })
+    }
+}
+
+impl Shape {
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Circle(circle) => circle.area(),
+            Shape::Square(square) => square.area(),
+            Shape::Polygon(polygon) => polygon.area(),
+        }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self {
+            Shape::Circle(circle) => circle.bounding_box(),
+            Shape::Square(square) => square.bounding_box(),
+            Shape::Polygon(polygon) => polygon.bounding_box(),
+        }
+    }
+}
+
+impl BoundingBox {
+    pub fn union(self, other: BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.x as i64 - self.min.x as i64
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.y as i64 - self.min.y as i64
+    }
+}
+
+impl Scene {
+    // Overlapping shapes are counted once each
+    pub fn total_area(&self) -> f64 {
+        self.shapes.iter().map(Shape::area).sum()
+    }
+
+    // `None` for an empty scene
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.shapes
+            .iter()
+            .map(Shape::bounding_box)
+            .reduce(BoundingBox::union)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Circle of radius {}", self.radius)?;
+        if self.center != Point::default() {
+            write!(f, " at {}", self.center)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Square of side {}", self.side)?;
+        if self.corner != Point::default() {
+            write!(f, " at {}", self.corner)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Polygon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Polygon of points ")?;
+        for (i, point) in self.points.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", point)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shape::Circle(circle) => circle.fmt(f),
+            Shape::Square(square) => square.fmt(f),
+            Shape::Polygon(polygon) => polygon.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for BoundingBox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "from {} to {}", self.min, self.max)
+    }
+}
+
+// A shape per line, parses back to the same scene
+impl fmt::Display for Scene {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for shape in &self.shapes {
+            writeln!(f, "{}", shape)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParseShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+
+        match &self.kind {
+            ParseShapeErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ParseShapeErrorKind::UnknownShape(name) => write!(
+                f,
+                "unknown shape `{}`, expected `Circle`, `Square` or `Polygon`",
+                name
+            ),
+            ParseShapeErrorKind::NumberTooLarge => write!(f, "number too large"),
+            ParseShapeErrorKind::NegativeSize => write!(f, "sizes can't be negative"),
+            ParseShapeErrorKind::TooFewPoints(n) => {
+                write!(f, "polygons need at least 3 points, found {}", n)
+            }
+            ParseShapeErrorKind::TrailingInput => write!(f, "expected the end of the shape"),
+        }
+    }
+}
+
+impl error::Error for ParseShapeError {}
+
+impl fmt::Display for ParseSceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, {}", self.line, self.error)
+    }
+}
+
+impl error::Error for ParseSceneError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// Reads one line, words are separated by any whitespace
+struct Parser<'a> {
+    line: &'a str,
+    // In bytes
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(line: &'a str) -> Parser<'a> {
+        Parser { line, position: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.line[self.position..]
+    }
+
+    fn error(&self, kind: ParseShapeErrorKind) -> ParseShapeError {
+        ParseShapeError {
+            column: self.line[..self.position].chars().count() + 1,
+            kind,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    // Whether the next token is `token`, which is then skipped
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+        let is_word = token.chars().all(char::is_alphabetic);
+        let matches = rest.starts_with(token)
+            && !(is_word && rest[token.len()..].starts_with(char::is_alphanumeric));
+
+        if matches {
+            self.position += token.len();
+        }
+
+        matches
+    }
+
+    fn expect(&mut self, token: &str, what: &'static str) -> Result<(), ParseShapeError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(ParseShapeErrorKind::Expected(what)))
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+
+        &rest[..len]
+    }
+
+    fn integer(&mut self) -> Result<i32, ParseShapeError> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .count();
+
+        if digits == 0 {
+            return Err(self.error(ParseShapeErrorKind::Expected("a number")));
+        }
+
+        let value = rest[..sign + digits]
+            .parse()
+            .map_err(|_| self.error(ParseShapeErrorKind::NumberTooLarge))?;
+        self.position += sign + digits;
+
+        Ok(value)
+    }
+
+    fn size(&mut self) -> Result<i32, ParseShapeError> {
+        let start = self.position;
+        let size = self.integer()?;
+
+        if size < 0 {
+            self.position = start;
+            self.skip_whitespace();
+            return Err(self.error(ParseShapeErrorKind::NegativeSize));
+        }
+
+        Ok(size)
+    }
+
+    fn point(&mut self) -> Result<Point, ParseShapeError> {
+        self.expect("(", "a point, e.g. `(1, 2)`")?;
+        let x = self.integer()?;
+        self.expect(",", "`,`")?;
+        let y = self.integer()?;
+        self.expect(")", "`)`")?;
+
+        Ok(Point { x, y })
+    }
+
+    // ` at (x, y)` or nothing
+    fn position(&mut self) -> Result<Point, ParseShapeError> {
+        if self.eat("at") {
+            self.point()
+        } else {
+            Ok(Point::default())
+        }
+    }
+
+    // After `Circle`
+    fn circle(&mut self) -> Result<Circle, ParseShapeError> {
+        self.expect("of", "`of`")?;
+        self.expect("radius", "`radius`")?;
+        let radius = self.size()?;
+        let center = self.position()?;
+
+        Ok(Circle { radius, center })
+    }
+
+    // After `Square`
+    fn square(&mut self) -> Result<Square, ParseShapeError> {
+        self.expect("of", "`of`")?;
+        self.expect("side", "`side`")?;
+        let side = self.size()?;
+        let corner = self.position()?;
+
+        Ok(Square { side, corner })
+    }
+
+    // After `Polygon`
+    fn polygon(&mut self) -> Result<Polygon, ParseShapeError> {
+        self.expect("of", "`of`")?;
+        self.expect("points", "`points`")?;
+
+        let start = self.position;
+        let mut points = vec![self.point()?];
+        while self.eat(",") {
+            points.push(self.point()?);
+        }
+
+        if points.len() < 3 {
+            self.position = start;
+            self.skip_whitespace();
+            return Err(self.error(ParseShapeErrorKind::TooFewPoints(points.len())));
+        }
+
+        Ok(Polygon { points })
+    }
+
+    fn shape(&mut self) -> Result<Shape, ParseShapeError> {
+        let start = self.position;
+
+        match self.word() {
+            "Circle" => {
+                self.eat("Circle");
+                self.circle().map(Shape::Circle)
+            }
+            "Square" => {
+                self.eat("Square");
+                self.square().map(Shape::Square)
+            }
+            "Polygon" => {
+                self.eat("Polygon");
+                self.polygon().map(Shape::Polygon)
+            }
+            "" => Err(self.error(ParseShapeErrorKind::Expected("a shape"))),
+            name => {
+                let error = self.error(ParseShapeErrorKind::UnknownShape(name.to_owned()));
+                self.position = start;
+                Err(error)
+            }
+        }
+    }
+
+    fn end(&mut self) -> Result<(), ParseShapeError> {
+        self.skip_whitespace();
+
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(ParseShapeErrorKind::TrailingInput))
+        }
+    }
+}
+
+// `$name` followed by what `$parse` reads, and nothing else
+macro_rules! impl_from_str {
+    ($($t:ident: $name:literal, $parse:ident;)*) => {
+        $(
+            impl FromStr for $t {
+                type Err = ParseShapeError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    let mut parser = Parser::new(s);
+                    parser.expect($name, concat!("`", $name, "`"))?;
+                    let shape = parser.$parse()?;
+                    parser.end()?;
+
+                    Ok(shape)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_str! {
+    Circle: "Circle", circle;
+    Square: "Square", square;
+    Polygon: "Polygon", polygon;
+}
+
+impl FromStr for Shape {
+    type Err = ParseShapeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let shape = parser.shape()?;
+        parser.end()?;
+
+        Ok(shape)
+    }
+}
+
+impl FromStr for Scene {
+    type Err = ParseSceneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shapes = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let shape = line
+                .parse()
+                .map_err(|error| ParseSceneError { line: i + 1, error })?;
+            shapes.push(shape);
+        }
+
+        Ok(Scene { shapes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(s: &str) -> String {
+        s.parse::<Shape>().unwrap_err().to_string()
+    }
+
+    // The `Circle` of 6.3
+    #[test]
+    fn test_section_circle() {
+        let circle = Circle::new(6).unwrap();
+
+        assert_eq!(circle.to_string(), "Circle of radius 6");
+        assert_eq!("Circle of radius 6".parse::<Circle>(), Ok(circle));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let shapes = [
+            Shape::Circle(Circle::new(2).unwrap().at(Point::new(5, -1))),
+            Shape::Square(Square::new(4).unwrap()),
+            Shape::Square(Square::new(0).unwrap().at(Point::new(-3, 0))),
+            Shape::Polygon(
+                Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(0, 3)]).unwrap(),
+            ),
+        ];
+
+        for shape in &shapes {
+            assert_eq!(shape.to_string().parse::<Shape>().as_ref(), Ok(shape));
+        }
+
+        assert_eq!(
+            "  Square   of side\t4 at(1,2) ".parse::<Square>(),
+            Ok(Square::new(4).unwrap().at(Point::new(1, 2)))
+        );
+    }
+
+    #[test]
+    fn test_invariants() {
+        assert_eq!(Circle::new(-6), None);
+        assert_eq!(Square::new(-1), None);
+        assert_eq!(Polygon::new(vec![]), None);
+        assert_eq!(Polygon::new(vec![Point::new(0, 0), Point::new(1, 1)]), None);
+
+        let square = Square::new(0).unwrap().at(Point::new(1, 2));
+        assert_eq!((square.side(), square.corner()), (0, Point::new(1, 2)));
+        let circle = Circle::new(3).unwrap();
+        assert_eq!((circle.radius(), circle.center()), (3, Point::default()));
+        let points = vec![Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)];
+        assert_eq!(Polygon::new(points.clone()).unwrap().points(), &points[..]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error("Circle of 6"), "column 11: expected `radius`");
+        assert_eq!(
+            error("Circle of radius six"),
+            "column 18: expected a number"
+        );
+        assert_eq!(
+            error("Circle of radius -6"),
+            "column 18: sizes can't be negative"
+        );
+        assert_eq!(
+            error("Circle of radius 99999999999"),
+            "column 18: number too large"
+        );
+        assert_eq!(
+            error("Triangle of side 3"),
+            "column 1: unknown shape `Triangle`, expected `Circle`, `Square` or `Polygon`"
+        );
+        assert_eq!(error(""), "column 1: expected a shape");
+        assert_eq!(
+            error("Circles of radius 1"),
+            "column 1: unknown shape `Circles`, expected `Circle`, `Square` or `Polygon`"
+        );
+        assert_eq!(
+            error("Square of side 2 at (1 2)"),
+            "column 24: expected `,`"
+        );
+        assert_eq!(
+            error("Square of side 2 at"),
+            "column 20: expected a point, e.g. `(1, 2)`"
+        );
+        assert_eq!(
+            error("Square of side 2 wide"),
+            "column 18: expected the end of the shape"
+        );
+        assert_eq!(
+            error("Polygon of points (0, 0), (1, 1)"),
+            "column 19: polygons need at least 3 points, found 2"
+        );
+        // Columns count characters
+        assert_eq!(error("Circle of radius é"), "column 18: expected a number");
+
+        assert_eq!(
+            "Square of side 1"
+                .parse::<Circle>()
+                .unwrap_err()
+                .to_string(),
+            "column 1: expected `Circle`"
+        );
+    }
+
+    #[test]
+    fn test_area_and_bounds() {
+        let triangle: Polygon = "Polygon of points (0, 0), (4, 0), (0, 3)".parse().unwrap();
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(
+            triangle.bounding_box(),
+            BoundingBox {
+                min: Point::new(0, 0),
+                max: Point::new(4, 3)
+            }
+        );
+
+        assert_eq!(Square::new(3).unwrap().area(), 9.0);
+        assert!((Circle::new(1).unwrap().area() - PI).abs() < 1e-12);
+
+        let bounds = Circle::new(2).unwrap().at(Point::new(5, -1)).bounding_box();
+        assert_eq!(bounds.to_string(), "from (3, -3) to (7, 1)");
+        assert_eq!((bounds.width(), bounds.height()), (4, 4));
+
+        let text = "Polygon of points (-2147483648, -2147483648), (2147483647, -2147483648), \
+                    (2147483647, 2147483647), (-2147483648, 2147483647)";
+        let side = u32::MAX as f64;
+        let polygon: Polygon = text.parse().unwrap();
+        assert_eq!(polygon.area(), side * side);
+        let scene: Scene = text.parse().unwrap();
+        assert_eq!(scene.total_area(), side * side);
+    }
+
+    #[test]
+    fn test_scene() {
+        let text = "\
+# A scene
+Circle of radius 1 at (10, 10)
+
+Square of side 2 at (-4, 0)
+  Polygon of points (0, 0), (4, 0), (0, 3)
+";
+        let scene: Scene = text.parse().unwrap();
+
+        assert_eq!(scene.shapes.len(), 3);
+        assert!((scene.total_area() - (PI + 4.0 + 6.0)).abs() < 1e-12);
+        assert_eq!(
+            scene.bounding_box().unwrap().to_string(),
+            "from (-4, 0) to (11, 11)"
+        );
+        assert_eq!(scene.to_string().parse(), Ok(scene));
+        assert_eq!(Scene::default().bounding_box(), None);
+    }
+
+    #[test]
+    fn test_scene_errors() {
+        let text = "Circle of radius 1\n\n  Square of side x\n";
+        let error = text.parse::<Scene>().unwrap_err();
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.error.column, 18);
+        assert_eq!(error.to_string(), "line 3, column 18: expected a number");
+    }
+}
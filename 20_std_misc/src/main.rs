@@ -651,7 +651,10 @@ match_args {{increase|decrease}} <integer>
     // https://doc.rust-lang.org/stable/rust-by-example/std_misc/ffi.html
     println!("\n--- 20.8. Foreign Function Interface ---");
     {
-        use std::fmt;
+        // NOTE: the minimal `Complex` became the generic `Complex<T>` of
+        //       01_hello, it is still `#[repr(C)]`, so `Complex<f32>` is a
+        //       C99 `float complex`
+        use _1_hello::complex::Complex;
 
         // NOTE: add cfg!
         // this extern block links to the libm library
@@ -660,56 +663,57 @@ match_args {{increase|decrease}} <integer>
         extern "C" {
             // this is a foreign function
             // that computes the square root of a single precision complex number
-            fn csqrtf(z: Complex) -> Complex;
+            fn csqrtf(z: Complex<f32>) -> Complex<f32>;
 
-            fn ccosf(z: Complex) -> Complex;
+            fn ccosf(z: Complex<f32>) -> Complex<f32>;
         }
 
+        // NOTE: without libm fall back to the pure Rust implementations
         #[cfg(not(unix))]
-        fn ccosf(v: Complex) -> Complex {
-            // dummy implementation
-            v
+        unsafe fn ccosf(z: Complex<f32>) -> Complex<f32> {
+            z.cos()
         }
 
         #[cfg(not(unix))]
-        fn csqrtf(v: Complex) -> Complex {
-            // dummy implementation
-            v
+        unsafe fn csqrtf(z: Complex<f32>) -> Complex<f32> {
+            z.sqrt()
         }
 
         // Since calling foreign functions is considered unsafe,
         // it's common to write safe wrappers around them.
-        fn cos(z: Complex) -> Complex {
+        fn cos(z: Complex<f32>) -> Complex<f32> {
             unsafe { ccosf(z) }
         }
 
         // z = -1 + 0i
-        let z = Complex { re: -1., im: 0. };
+        let z = Complex::new(-1.0f32, 0.);
 
         // calling a foreign function is an unsafe operation
         let z_sqrt = unsafe { csqrtf(z) };
 
-        println!("the square root of {:?} is {:?}", z, z_sqrt);
+        println!("the square root of {} is {}", z, z_sqrt);
 
         // calling safe API wrapped around unsafe operation
-        println!("cos({:?}) = {:?}", z, cos(z));
-
-        // Minimal implementation of single precision complex numbers
-        #[repr(C)]
-        #[derive(Clone, Copy)]
-        struct Complex {
-            re: f32,
-            im: f32,
-        }
-
-        impl fmt::Debug for Complex {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                if self.im < 0. {
-                    write!(f, "{}-{}i", self.re, -self.im)
-                } else {
-                    write!(f, "{}+{}i", self.re, self.im)
-                }
-            }
-        }
+        println!("cos({}) = {}", z, cos(z));
+
+        // NOTE: the pure Rust versions agree with libm
+        println!("pure Rust: sqrt({}) = {}", z, z.sqrt());
+        println!("pure Rust: cos({}) = {}", z, z.cos());
+
+        let w = Complex::new(0.5f32, -1.5);
+        println!(
+            "libm: sqrt({}) = {}, cos({}) = {}",
+            w,
+            unsafe { csqrtf(w) },
+            w,
+            cos(w)
+        );
+        println!(
+            "pure Rust: sqrt({}) = {}, cos({}) = {}",
+            w,
+            w.sqrt(),
+            w,
+            w.cos()
+        );
     }
 }